@@ -3,7 +3,15 @@ use pm::pre::*;
 pub fn expand(attr: TokenStream, input: TokenStream) -> pm::Result<TokenStream2> {
     let attrs = parse_attributes(attr)?;
 
-    let mut item: syn::ItemFn = syn::parse(input)?;
+    let item: syn::Item = syn::parse(input)?;
+    match item {
+        syn::Item::Fn(item) => expand_main(attrs, item),
+        syn::Item::Enum(item) => expand_subcommand(attrs, item),
+        item => pm::bail!(item, "expecting the main function or a subcommand enum"),
+    }
+}
+
+fn expand_main(attrs: ParsedAttributes, mut item: syn::ItemFn) -> pm::Result<TokenStream2> {
     let is_async = item.sig.asyncness.is_some();
 
     let generated_main_name = {
@@ -23,10 +31,8 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> pm::Result<TokenStream2>
         None => pm::quote! { (|_| {}) },
     };
 
-    let fn_log_config_impl = match attrs.log_config {
-        Some(value) => pm::quote! { { #value } },
-        None => pm::quote! { |_| cu::cli::DefaultLogConfig  },
-    };
+    let fn_log_config_impl = log_config_impl(attrs.log_config);
+    let env_prefix_impl = env_prefix_impl(attrs.env_prefix);
 
     let main_impl = if is_async {
         pm::quote! {
@@ -34,7 +40,8 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> pm::Result<TokenStream2>
                 #fn_preproc_impl,
                 #fn_log_config_impl,
                 #generated_main_name,
-                #fn_flag_impl
+                #fn_flag_impl,
+                #env_prefix_impl
             )
         }
     } else {
@@ -43,7 +50,8 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> pm::Result<TokenStream2>
                 #fn_preproc_impl,
                 #fn_log_config_impl,
                 #generated_main_name,
-                #fn_flag_impl
+                #fn_flag_impl,
+                #env_prefix_impl
             )
         }
     };
@@ -64,6 +72,197 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> pm::Result<TokenStream2>
     Ok(expanded)
 }
 
+/// Generate the main function that parses the flags and the subcommand,
+/// then calls the handler of the subcommand
+fn expand_subcommand(attrs: ParsedAttributes, mut item: syn::ItemEnum) -> pm::Result<TokenStream2> {
+    if let Some(flags) = attrs.flags_ident {
+        pm::bail!(flags, "`flags` is not supported on subcommand enum");
+    }
+    if let Some(preprocess) = attrs.preprocess_fn {
+        pm::bail!(
+            preprocess,
+            "`preprocess` is not supported on subcommand enum"
+        );
+    }
+    if !item.generics.params.is_empty() {
+        pm::bail!(item.generics, "subcommand enum cannot be generic");
+    }
+
+    let enum_ident = &item.ident;
+    let mut is_async = false;
+    let mut match_arms = Vec::with_capacity(item.variants.len());
+    for variant in &mut item.variants {
+        let handler = parse_handler(variant)?;
+        is_async |= handler.is_async;
+        let path = handler.path;
+        let call_await = if handler.is_async {
+            pm::quote! { .await }
+        } else {
+            pm::quote! {}
+        };
+        let variant_ident = &variant.ident;
+        let arm = match &variant.fields {
+            syn::Fields::Unit => pm::quote! {
+                #enum_ident::#variant_ident => #path(flags) #call_await,
+            },
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => pm::quote! {
+                #enum_ident::#variant_ident(args) => #path(args, flags) #call_await,
+            },
+            fields => pm::bail!(
+                fields,
+                "subcommand must be a unit variant or a tuple variant with one field"
+            ),
+        };
+        match_arms.push(arm);
+    }
+
+    let cli_ident = pm::format_ident!("__Cu{enum_ident}");
+    // the doc comments of the enum are used as the help text of the program
+    let docs = item.attrs.iter().filter(|x| x.path().is_ident("doc"));
+    let fn_log_config_impl = log_config_impl(attrs.log_config);
+    let env_prefix_impl = env_prefix_impl(attrs.env_prefix);
+
+    let main_impl = if is_async {
+        pm::quote! {
+            cu::cli::__co_run(
+                |_| {},
+                #fn_log_config_impl,
+                |x: #cli_ident| async move {
+                    let flags = x.flags;
+                    match x.command { #(#match_arms)* }
+                },
+                |x| &x.flags,
+                #env_prefix_impl
+            )
+        }
+    } else {
+        pm::quote! {
+            cu::cli::__run(
+                |_| {},
+                #fn_log_config_impl,
+                |x: #cli_ident| {
+                    let flags = x.flags;
+                    match x.command { #(#match_arms)* }
+                },
+                |x| &x.flags,
+                #env_prefix_impl
+            )
+        }
+    };
+
+    let expanded = pm::quote! {
+        #item
+        #(#docs)*
+        #[derive(clap::Parser)]
+        // make the flags global, so they can be specified after the subcommand
+        #[command(mut_args = |arg: clap::Arg| arg.global(true))]
+        #[doc(hidden)]
+        struct #cli_ident {
+            #[clap(subcommand)]
+            command: #enum_ident,
+            #[clap(flatten)]
+            flags: cu::cli::Flags,
+        }
+        fn main() -> std::process::ExitCode {
+            unsafe { #main_impl }
+        }
+    };
+
+    Ok(expanded)
+}
+
+/// Take the `#[cu(...)]` attribute out of the variant and parse the handler
+fn parse_handler(variant: &mut syn::Variant) -> pm::Result<Handler> {
+    let mut path = None;
+    let mut is_async = false;
+    let mut error = None;
+    variant.attrs.retain(|attr| {
+        if !attr.path().is_ident("cu") {
+            return true;
+        }
+        let args = attr.parse_args_with(
+            syn::punctuated::Punctuated::<HandlerArg, syn::Token![,]>::parse_terminated,
+        );
+        match args {
+            Ok(args) => {
+                for arg in args {
+                    match arg {
+                        HandlerArg::Async => is_async = true,
+                        HandlerArg::Handler(x) => path = Some(x),
+                    }
+                }
+            }
+            Err(e) => error = Some(e),
+        }
+        false
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let name = to_snake_case(&variant.ident.to_string());
+            syn::Ident::new(&name, variant.ident.span()).into()
+        }
+    };
+    Ok(Handler { path, is_async })
+}
+
+struct Handler {
+    path: syn::Path,
+    is_async: bool,
+}
+
+enum HandlerArg {
+    Async,
+    Handler(syn::Path),
+}
+
+impl syn::parse::Parse for HandlerArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::Token![async]) {
+            input.parse::<syn::Token![async]>()?;
+            return Ok(Self::Async);
+        }
+        let name: syn::Ident = input.parse()?;
+        if name != "handler" {
+            pm::bail!(name, "unknown attribute");
+        }
+        input.parse::<syn::Token![=]>()?;
+        Ok(Self::Handler(input.parse()?))
+    }
+}
+
+fn to_snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn log_config_impl(log_config: Option<syn::Expr>) -> TokenStream2 {
+    match log_config {
+        Some(value) => pm::quote! { { #value } },
+        None => pm::quote! { |_| cu::cli::DefaultLogConfig  },
+    }
+}
+
+fn env_prefix_impl(env_prefix: Option<syn::LitStr>) -> TokenStream2 {
+    match env_prefix {
+        Some(prefix) => pm::quote! { Some(#prefix) },
+        None => pm::quote! { None },
+    }
+}
+
 fn parse_attributes(attr: TokenStream) -> pm::Result<ParsedAttributes> {
     let attrs = pm::parse_punctuated::<syn::MetaNameValue, syn::Token![,]>(attr)?;
     let mut out = ParsedAttributes::default();
@@ -89,6 +288,17 @@ fn parse_attributes(attr: TokenStream) -> pm::Result<ParsedAttributes> {
             out.log_config = Some(attr.value);
             continue;
         }
+        if attr.path.is_ident("env_prefix") {
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(s),
+                ..
+            }) = attr.value
+            else {
+                pm::bail!(attr.value, "expecting string literal");
+            };
+            out.env_prefix = Some(s);
+            continue;
+        }
         pm::bail!(attr, "unknown attribute");
     }
     Ok(out)
@@ -98,4 +308,5 @@ struct ParsedAttributes {
     flags_ident: Option<syn::Ident>,
     preprocess_fn: Option<syn::Expr>,
     log_config: Option<syn::Expr>,
+    env_prefix: Option<syn::LitStr>,
 }
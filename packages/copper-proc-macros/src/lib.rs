@@ -97,6 +97,52 @@ use pm::pre::*;
 /// }
 /// ```
 ///
+/// ## Subcommands
+///
+/// The macro can also annotate a [`clap::Subcommand`](https://docs.rs/clap/latest/clap/trait.Subcommand.html)
+/// enum instead of the main function. This generates the `main` function, which parses
+/// the subcommand along with `cu::cli::Flags`, and calls the handler of the subcommand.
+/// The flags are global, so both `-v build` and `build -v` will work.
+///
+/// Each variant should be a unit variant or a tuple variant with one field.
+/// The handler takes the field (if any) and the `cu::cli::Flags`, and returns a `cu::Result`.
+/// By default, the handler is the function with the snake case name of the variant,
+/// which can be changed with `#[cu(handler = path)]`. Add `async` to the attribute
+/// if the handler is async (requires the `coroutine` feature).
+///
+/// Doc comments on the enum are used as the help text of the program.
+///
+/// Note `#[cu::cli]` must be placed above the `derive`.
+///
+/// ```rust,ignore
+/// # use pistonite_cu as cu;
+/// use cu::pre::*;
+///
+/// /// My program
+/// #[cu::cli]
+/// #[derive(clap::Subcommand)]
+/// enum Command {
+///     /// Build the project
+///     Build(BuildArgs),
+///     /// Remove the build outputs
+///     #[cu(handler = clean_outputs, async)]
+///     Clean,
+/// }
+/// #[derive(clap::Args)]
+/// struct BuildArgs {
+///     #[clap(long)]
+///     release: bool,
+/// }
+/// fn build(args: BuildArgs, flags: cu::cli::Flags) -> cu::Result<()> {
+///     Ok(())
+/// }
+/// async fn clean_outputs(flags: cu::cli::Flags) -> cu::Result<()> {
+///     Ok(())
+/// }
+/// ```
+///
+/// The `preprocess` and `flags` attributes are not supported for subcommands.
+///
 /// ## Attributes
 ///
 /// ### `preprocess`
@@ -104,7 +150,8 @@ use pm::pre::*;
 /// The attribute can also take a `preprocess` function
 /// to process flags before initializing the CLI system.
 /// This can be useful to merge multiple Flags instance
-/// in the CLI. Note that the logging/printing system
+/// in the CLI (though [subcommands](#subcommands) are easier for this).
+/// Note that the logging/printing system
 /// will not work during the preprocess.
 ///
 /// ```rust,ignore
@@ -179,6 +226,20 @@ use pm::pre::*;
 ///     Ok(())
 /// }
 /// ```
+///
+/// ### `env_prefix`
+/// `env_prefix` enables reading options that are not specified on the command line
+/// from environment variables with the prefix, for example `MYTOOL_VERBOSE=1`
+/// or `MYTOOL_COLOR=never`. This is useful when the program is called by other scripts
+/// that cannot easily pass flags. See `cu::cli::Flags::apply_env` for the full list.
+///
+/// ```rust,ignore
+/// # use pistonite_cu as cu;
+/// #[cu::cli(env_prefix = "MYTOOL")]
+/// fn main(_: cu::cli::Flags) -> cu::Result<()> {
+///     Ok(())
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn cli(attr: TokenStream, input: TokenStream) -> TokenStream {
     pm::flatten(cli::expand(attr, input))
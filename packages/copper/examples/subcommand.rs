@@ -0,0 +1,36 @@
+use cu::pre::*;
+use pistonite_cu as cu;
+
+/// Example of subcommand dispatch
+///
+/// The global flags like `-v` can be specified before or after the subcommand
+#[cu::cli]
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Build the project (sync handler)
+    Build(BuildArgs),
+    /// Remove build outputs (async handler)
+    #[cu(handler = clean_outputs, async)]
+    Clean,
+}
+
+#[derive(clap::Args)]
+struct BuildArgs {
+    /// Build in release mode
+    #[clap(long)]
+    release: bool,
+}
+
+fn build(args: BuildArgs, flags: cu::cli::Flags) -> cu::Result<()> {
+    cu::info!("building, release={}", args.release);
+    cu::debug!("flags are {flags:?}");
+    Ok(())
+}
+
+async fn clean_outputs(_: cu::cli::Flags) -> cu::Result<()> {
+    cu::info!("cleaning");
+    cu::co::spawn(async { cu::info!("cleaned") })
+        .co_join()
+        .await?;
+    Ok(())
+}
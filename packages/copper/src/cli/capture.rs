@@ -0,0 +1,190 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::cli::printer::{PRINTER, Printer};
+#[cfg(feature = "prompt")]
+use crate::cli::prompt::PROMPT_LEVEL;
+use crate::lv;
+
+/// Only one capture can be active at a time, since the printer is global
+static CAPTURE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Capture printed output in memory, for testing
+///
+/// While the returned [`Capture`] is alive, the global printer is swapped
+/// with one that records the messages instead of printing them to the terminal.
+/// The previous printer is restored when the `Capture` is dropped.
+///
+/// Messages are recorded if they would be printed at the current print level.
+/// Use [`cu::cli::level`](crate::cli::level) before capturing to change the level.
+/// If logging is not initialized yet, it will be initialized with the default level.
+///
+/// Since the printer is global, only one capture can be active at a time.
+/// Calling this while another capture is active will block until the other one is dropped,
+/// so tests that use capture can run in parallel. However, messages printed
+/// by tests that are not capturing will also be recorded.
+///
+/// # Example
+/// ```rust
+/// # use pistonite_cu as cu;
+/// fn do_something() {
+///     cu::warn!("something is not right");
+///     cu::info!("done");
+/// }
+///
+/// let capture = cu::cli::capture();
+/// do_something();
+/// assert_eq!(capture.count(cu::lv::W), 1);
+/// assert_eq!(
+///     capture.messages(),
+///     vec![
+///         (cu::lv::W, "something is not right".to_string()),
+///         (cu::lv::I, "done".to_string()),
+///     ]
+/// );
+/// ```
+///
+/// # Progress bars
+/// Progress bars are not animated while capturing. The final state
+/// of each bar is recorded when the bar finishes, see [`Capture::bars`].
+///
+/// # Prompts
+/// Prompts are answered with answers queued with [`Capture::answer`], in order.
+/// Prompts are allowed while capturing, regardless of the prompt level.
+/// If there are no more answers, the prompt will fail with an error.
+pub fn capture() -> Capture {
+    let lock = CAPTURE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if !super::print_init::is_initialized() {
+        super::level("");
+    }
+    let recording = Arc::new(Mutex::new(Recording::default()));
+    let printer = Printer::new_capture(Arc::clone(&recording));
+    let previous_printer = match PRINTER.lock() {
+        Ok(mut g_printer) => g_printer.replace(printer),
+        Err(_) => None,
+    };
+    #[cfg(feature = "prompt")]
+    let previous_prompt_level = {
+        let level = PROMPT_LEVEL.get();
+        PROMPT_LEVEL.set(lv::Prompt::Interactive);
+        level
+    };
+    Capture {
+        recording,
+        previous_printer,
+        #[cfg(feature = "prompt")]
+        previous_prompt_level,
+        _lock: lock,
+    }
+}
+
+/// Guard for capturing printed output. See [`capture`]
+pub struct Capture {
+    recording: Arc<Mutex<Recording>>,
+    previous_printer: Option<Printer>,
+    #[cfg(feature = "prompt")]
+    previous_prompt_level: lv::Prompt,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Capture {
+    /// Get the recorded messages and their levels, in the order they are printed
+    pub fn messages(&self) -> Vec<(lv::Lv, String)> {
+        self.with_recording(|x| x.messages.clone())
+    }
+
+    /// Get the number of recorded messages at the level
+    pub fn count(&self, lv: lv::Lv) -> usize {
+        self.with_recording(|x| x.messages.iter().filter(|(l, _)| *l == lv).count())
+    }
+
+    /// Get the data printed with [`cu::output!`](crate::output)
+    pub fn output(&self) -> String {
+        self.with_recording(|x| x.output.clone())
+    }
+
+    /// Get the final states of progress bars, in the order they finished
+    pub fn bars(&self) -> Vec<CapturedBar> {
+        self.with_recording(|x| x.bars.clone())
+    }
+
+    /// Get the prompts shown and their answers, in the order they are shown
+    #[cfg(feature = "prompt")]
+    pub fn prompts(&self) -> Vec<CapturedPrompt> {
+        self.with_recording(|x| x.prompts.clone())
+    }
+
+    /// Queue an answer for the next prompt
+    ///
+    /// # Example
+    /// ```rust
+    /// # use pistonite_cu as cu;
+    /// let capture = cu::cli::capture();
+    /// capture.answer("y");
+    /// assert!(cu::yesno!("continue?").unwrap());
+    /// assert_eq!(capture.prompts()[0].answer.as_deref(), Some("y"));
+    /// ```
+    #[cfg(feature = "prompt")]
+    pub fn answer(&self, answer: impl Into<String>) -> &Self {
+        let answer = answer.into();
+        self.with_recording(|x| x.answers.push_back(answer));
+        self
+    }
+
+    /// Clear everything recorded so far
+    pub fn clear(&self) {
+        self.with_recording(|x| {
+            x.messages.clear();
+            x.bars.clear();
+            x.output.clear();
+            #[cfg(feature = "prompt")]
+            x.prompts.clear();
+        })
+    }
+
+    fn with_recording<T, F: FnOnce(&mut Recording) -> T>(&self, f: F) -> T {
+        let mut recording = self.recording.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut recording)
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if let Ok(mut g_printer) = PRINTER.lock() {
+            *g_printer = self.previous_printer.take();
+        }
+        #[cfg(feature = "prompt")]
+        PROMPT_LEVEL.set(self.previous_prompt_level);
+    }
+}
+
+/// Final state of a progress bar, recorded by [`Capture`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapturedBar {
+    /// The bar is done. The done message is `None` if the bar is not kept
+    Done(Option<String>),
+    /// The bar is interrupted, with the interrupted message
+    Interrupted(String),
+}
+
+/// A prompt shown while capturing, recorded by [`Capture`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "prompt")]
+pub struct CapturedPrompt {
+    /// The prompt message
+    pub prompt: String,
+    /// If the prompt is a password prompt
+    pub is_password: bool,
+    /// The answer, `None` if there was no answer queued
+    pub answer: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct Recording {
+    pub messages: Vec<(lv::Lv, String)>,
+    pub bars: Vec<CapturedBar>,
+    pub output: String,
+    #[cfg(feature = "prompt")]
+    pub prompts: Vec<CapturedPrompt>,
+    #[cfg(feature = "prompt")]
+    pub answers: std::collections::VecDeque<String>,
+}
@@ -1,4 +1,5 @@
 use std::ffi::OsString;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -19,6 +20,15 @@ pub struct Flags {
     /// Set the color mode for this program. May affect subprocesses spawned.
     #[clap(long)]
     pub color: Option<lv::Color>,
+    /// Set the charset of the glyphs, such as spinners and tree lines
+    #[clap(long)]
+    pub charset: Option<lv::Charset>,
+    /// Set the format of the messages printed by this program
+    #[clap(long, value_name = "FORMAT")]
+    pub log_format: Option<lv::Format>,
+    /// Also write all messages to a file, regardless of verbosity
+    #[clap(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
     /// Automatically answer 'yes' to all yes/no prompts
     #[cfg(feature = "prompt")]
     #[clap(short = 'y', long)]
@@ -31,6 +41,34 @@ pub struct Flags {
     #[cfg(feature = "prompt")]
     #[clap(long, action(clap::ArgAction::Count))]
     pub interactive: u8,
+    /// Print the shell completion script for this program and exit
+    #[clap(long, value_name = "SHELL", hide = true, exclusive = true)]
+    pub generate_completion: Option<CompletionShell>,
+    /// Print the man page for this program and exit
+    #[clap(long, hide = true, exclusive = true)]
+    pub generate_man: bool,
+}
+
+/// Shell to generate the completion script for, with `--generate-completion`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Elvish,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+    Zsh,
+}
+impl CompletionShell {
+    fn to_clap(self) -> clap_complete::Shell {
+        match self {
+            Self::Bash => clap_complete::Shell::Bash,
+            Self::Elvish => clap_complete::Shell::Elvish,
+            Self::Fish => clap_complete::Shell::Fish,
+            Self::PowerShell => clap_complete::Shell::PowerShell,
+            Self::Zsh => clap_complete::Shell::Zsh,
+        }
+    }
 }
 
 impl AsRef<Flags> for Flags {
@@ -82,7 +120,74 @@ impl Flags {
                 None
             }
         };
-        super::print_init::init_options(self.color.unwrap_or_default(), level, prompt, log_config);
+        super::print_init::init_options(super::print_init::PrintOptions {
+            color: self.color.unwrap_or_default(),
+            level,
+            format: self.log_format,
+            prompt,
+            log_config,
+            log_file: self.log_file.as_deref(),
+        });
+        lv::set_charset(self.charset.unwrap_or_default());
+    }
+
+    /// Fill in options not specified on the command line from environment variables
+    ///
+    /// For example, with the prefix `MYTOOL`, the following variables are read:
+    /// - `MYTOOL_VERBOSE` and `MYTOOL_QUIET`: number of `-v` or `-q`. Only used if neither
+    ///   `-v` nor `-q` is specified
+    /// - `MYTOOL_COLOR`: same values as `--color`
+    /// - `MYTOOL_CHARSET`: same values as `--charset`
+    /// - `MYTOOL_LOG_FORMAT`: same values as `--log-format`
+    /// - `MYTOOL_LOG_FILE`: same as `--log-file`
+    /// - `MYTOOL_YES`: `1`/`true` or `0`/`false`, same as `--yes`
+    /// - `MYTOOL_NON_INTERACTIVE`: `1`/`true` or `0`/`false`, same as `--non-interactive`.
+    ///   Only used if neither `--interactive` nor `--non-interactive` is specified
+    ///
+    /// Variables that are not set or empty are ignored. Invalid values are also ignored,
+    /// and an error listing them is returned after the valid values are applied.
+    ///
+    /// The [`cu::cli`](macro@crate::cli) macro calls this when the `env_prefix` attribute is set.
+    pub fn apply_env(&mut self, prefix: &str) -> crate::Result<()> {
+        let mut env = EnvReader {
+            prefix,
+            invalid: vec![],
+        };
+        if self.verbose == 0 && self.quiet == 0 {
+            self.verbose = env.parse("VERBOSE", |x| x.parse().ok()).unwrap_or_default();
+            self.quiet = env.parse("QUIET", |x| x.parse().ok()).unwrap_or_default();
+        }
+        if self.color.is_none() {
+            self.color = env.parse("COLOR", |x| clap::ValueEnum::from_str(x, true).ok());
+        }
+        if self.charset.is_none() {
+            self.charset = env.parse("CHARSET", |x| clap::ValueEnum::from_str(x, true).ok());
+        }
+        if self.log_format.is_none() {
+            self.log_format = env.parse("LOG_FORMAT", |x| clap::ValueEnum::from_str(x, true).ok());
+        }
+        if self.log_file.is_none() {
+            self.log_file = env.parse("LOG_FILE", |x| Some(PathBuf::from(x)));
+        }
+        #[cfg(feature = "prompt")]
+        {
+            if !self.yes {
+                self.yes = env.parse("YES", parse_bool).unwrap_or_default();
+            }
+            if self.non_interactive == 0
+                && self.interactive == 0
+                && env.parse("NON_INTERACTIVE", parse_bool).unwrap_or_default()
+            {
+                self.non_interactive = 1;
+            }
+        }
+        if !env.invalid.is_empty() {
+            crate::bail!(
+                "ignored invalid environment variables: {}",
+                env.invalid.join(", ")
+            );
+        }
+        Ok(())
     }
 
     pub fn print_level(&self) -> lv::Print {
@@ -98,6 +203,21 @@ impl Flags {
         if let Some(color) = other.color {
             self.color = Some(color);
         }
+        if let Some(charset) = other.charset {
+            self.charset = Some(charset);
+        }
+        if let Some(log_format) = other.log_format {
+            self.log_format = Some(log_format);
+        }
+        if let Some(log_file) = &other.log_file {
+            self.log_file = Some(log_file.clone());
+        }
+        if let Some(shell) = other.generate_completion {
+            self.generate_completion = Some(shell);
+        }
+        if other.generate_man {
+            self.generate_man = true;
+        }
         #[cfg(feature = "prompt")]
         {
             if other.yes {
@@ -109,6 +229,45 @@ impl Flags {
     }
 }
 
+/// Helper to read environment variables for [`Flags::apply_env`]
+struct EnvReader<'a> {
+    prefix: &'a str,
+    /// Variables with invalid values
+    invalid: Vec<String>,
+}
+impl EnvReader<'_> {
+    /// Read `PREFIX_NAME` and parse it. Returns `None` if the variable
+    /// is not set, is empty, or is invalid
+    fn parse<T>(&mut self, name: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        // not using cu::env_var, since we are before log initialization
+        let name = format!("{}_{name}", self.prefix);
+        let value = match std::env::var(&name) {
+            Ok(value) => value,
+            Err(std::env::VarError::NotPresent) => return None,
+            Err(std::env::VarError::NotUnicode(_)) => {
+                self.invalid.push(name);
+                return None;
+            }
+        };
+        if value.is_empty() {
+            return None;
+        }
+        let parsed = parse(&value);
+        if parsed.is_none() {
+            self.invalid.push(format!("{name}={value}"));
+        }
+        parsed
+    }
+}
+#[cfg(feature = "prompt")]
+fn parse_bool(x: &str) -> Option<bool> {
+    match x.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
 /// Entry point to CLI
 ///
 /// # Safety
@@ -128,6 +287,7 @@ pub unsafe fn __run<
     fn_log_config: FLogConfig,
     fn_execute: FExecute,
     fn_flag: FFlag,
+    env_prefix: Option<&str>,
 ) -> std::process::ExitCode {
     let start = std::time::Instant::now();
     let args = unsafe {
@@ -135,8 +295,10 @@ pub unsafe fn __run<
             fn_preproc,
             fn_log_config,
             fn_flag,
+            env_prefix,
         )
     };
+    super::panic_hook::install();
     let result = fn_execute(args);
     handle_result(start, result)
 }
@@ -162,6 +324,7 @@ pub unsafe fn __co_run<
     fn_log_config: FLogConfig,
     fn_execute: FExecute,
     fn_flag: FFlag,
+    env_prefix: Option<&str>,
 ) -> std::process::ExitCode {
     let start = std::time::Instant::now();
     let args = unsafe {
@@ -169,8 +332,10 @@ pub unsafe fn __co_run<
             fn_preproc,
             fn_log_config,
             fn_flag,
+            env_prefix,
         )
     };
+    super::panic_hook::install();
     #[cfg(not(feature = "coroutine-heavy"))]
     let result = crate::co::block(async move { fn_execute(args).await });
     #[cfg(feature = "coroutine-heavy")]
@@ -189,24 +354,101 @@ unsafe fn parse_args_or_help<
     fn_preproc: FPreproc,
     fn_log_config: FLogConfig,
     fn_flag: FFlag,
+    env_prefix: Option<&str>,
 ) -> TArg {
-    let mut parsed = parse_args::<TArg>();
+    let mut parsed = parse_args::<TArg>(env_prefix);
     fn_preproc(&mut parsed);
     let flags = fn_flag(&parsed);
-    let log_config: Arc<dyn LogConfig + Send + Sync> = Arc::new(fn_log_config(flags));
+    let Some(env_prefix) = env_prefix else {
+        let log_config: Arc<dyn LogConfig + Send + Sync> = Arc::new(fn_log_config(flags));
+        unsafe { flags.apply(log_config) };
+        return parsed;
+    };
+    // the parsed args are not modified, env vars only affect the applied flags
+    let mut flags = flags.clone();
+    let env_result = flags.apply_env(env_prefix);
+    let log_config: Arc<dyn LogConfig + Send + Sync> = Arc::new(fn_log_config(&flags));
     unsafe { flags.apply(log_config) };
+    if let Err(e) = env_result {
+        crate::warn!("{e}");
+    }
     parsed
 }
 
+/// Handle the hidden `--generate-completion` and `--generate-man` options,
+/// and exit if any of them is specified.
+///
+/// The options are exclusive, so they work even if the required args are missing
+fn generate_and_exit<T: Parser>(flags: &Flags) {
+    let mut command = <T as CommandFactory>::command();
+    // use the name the program is invoked with, since the command name
+    // defaults to the package name, which could be different from the binary
+    let bin_name = std::env::args_os()
+        .next()
+        .and_then(|x| {
+            let path = PathBuf::from(x);
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| command.get_name().to_string());
+    let Some(out) = generate(flags, &mut command, bin_name) else {
+        return;
+    };
+    if let Err(e) = std::io::Write::write_all(&mut std::io::stdout(), &out) {
+        eprintln!("failed to write generated output: {e}");
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
+
+/// Generate the completion script or man page, if requested by the flags
+fn generate(flags: &Flags, command: &mut Command, bin_name: String) -> Option<Vec<u8>> {
+    // generate to a buffer first, since clap_complete panics if writing fails
+    let mut out = Vec::new();
+    if let Some(shell) = flags.generate_completion {
+        clap_complete::generate(shell.to_clap(), command, bin_name, &mut out);
+    } else if flags.generate_man {
+        let command = command.clone().display_name(&bin_name).bin_name(bin_name);
+        // writing to a Vec does not fail
+        let _ = clap_mangen::Man::new(command).render(&mut out);
+    } else {
+        return None;
+    }
+    Some(out)
+}
+
+/// Allow the command to be parsed without the subcommand. The exclusive options
+/// already skip the required args, but not the required subcommand
+fn relax_required(command: Command) -> Command {
+    command
+        .subcommand_required(false)
+        .arg_required_else_help(false)
+}
+
 /// Wrapper for clap parse to respect the color flag when printing help or error
-fn parse_args<T: Parser>() -> T {
+fn parse_args<T: Parser>(env_prefix: Option<&str>) -> T {
     // parse the color arg first, so that we can respect it when printing help
-    let color = lv::Color::from_os_args();
+    let mut color = lv::Color::from_os_args();
+    if color == lv::Color::Auto
+        && let Some(env_prefix) = env_prefix
+    {
+        let mut flags = Flags::default();
+        // errors are reported after parsing
+        let _ = flags.apply_env(env_prefix);
+        color = flags.color.unwrap_or_default();
+    }
     let use_color = color.is_colored_for_stdout();
 
+    // the flags are flattened into T, so they can be parsed from the same matches.
+    // this is done before the real parse, since the required args or subcommand
+    // could be missing. Errors are reported by the real parse
+    if let Ok(matches) = relax_required(<T as CommandFactory>::command()).try_get_matches()
+        && let Ok(flags) = Flags::from_arg_matches(&matches)
+    {
+        generate_and_exit::<T>(&flags);
+    }
+
     // this will exit on error
     let mut matches = get_colored_command::<T>(use_color).get_matches();
-
     match <T as FromArgMatches>::from_arg_matches_mut(&mut matches) {
         Ok(x) => x,
         Err(e) => {
@@ -290,10 +532,64 @@ fn get_colored_command_impl(command: Command, color: bool) -> Command {
     }
 }
 
+/// Print the error returned from the entry point, with the causes
+/// and the hints attached with [`cu::Hint`](crate::Hint)
+fn print_error_report(error: &crate::Error) {
+    use std::backtrace::BacktraceStatus;
+    use std::fmt::Write as _;
+
+    use crate::errhand::Hinted;
+
+    let mut report = format!("fatal: {error}");
+    for (i, cause) in error.chain().skip(1).enumerate() {
+        if i == 0 {
+            report.push_str("\ncaused by:");
+        }
+        let _ = write!(report, "\n  {}: {cause}", i + 1);
+    }
+    let hinted: Vec<&Hinted> = error
+        .chain()
+        .filter_map(|x| x.downcast_ref::<Hinted>())
+        .collect();
+    // the innermost one is the closest to where the error happened
+    let backtrace = match hinted.last() {
+        Some(innermost) => {
+            let location = innermost.location();
+            let _ = write!(
+                report,
+                "\nhint attached at {}:{}",
+                location.file(),
+                location.line()
+            );
+            innermost.inner().backtrace()
+        }
+        None => error.backtrace(),
+    };
+    if backtrace.status() == BacktraceStatus::Captured {
+        let _ = write!(report, "\n\nbacktrace:\n{backtrace}");
+    }
+    crate::error!("{report}");
+    for hinted in hinted.iter().rev() {
+        for hint in hinted.hints() {
+            crate::hint!("{hint}");
+        }
+    }
+}
+
 fn handle_result(start: Instant, result: crate::Result<()>) -> std::process::ExitCode {
     let elapsed = start.elapsed().as_secs_f32();
+    // recap before the fatal error, so the error is still the last thing displayed
+    super::summary::print_recap();
+    let counts = || {
+        let counts = super::summary::format_counts();
+        if counts.is_empty() {
+            counts
+        } else {
+            format!(" with {counts}")
+        }
+    };
     if let Err(e) = result {
-        crate::error!("fatal: {e:?}");
+        print_error_report(&e);
         // we display the hint for user to use -vv
         // if:
         // - the user is already tried to get more debug info with -v
@@ -312,13 +608,13 @@ fn handle_result(start: Instant, result: crate::Result<()>) -> std::process::Exi
         if crate::lv::is_print_time_enabled() {
             // use debug so the error trace is the last line,
             // so user is directed to see what is the most important
-            crate::debug!("finished in {elapsed:.2}s");
+            crate::debug!("finished in {elapsed:.2}s{}", counts());
         }
         reset_color();
         std::process::ExitCode::FAILURE
     } else {
         if crate::lv::is_print_time_enabled() {
-            crate::info!("finished in {elapsed:.2}s");
+            crate::info!("finished in {elapsed:.2}s{}", counts());
         }
         reset_color();
         std::process::ExitCode::SUCCESS
@@ -340,3 +636,91 @@ fn reset_color() {
         let _ = stderr.flush();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hint as _;
+
+    #[derive(Parser)]
+    struct TestArgs {
+        #[clap(flatten)]
+        flags: Flags,
+        name: String,
+    }
+
+    #[derive(Parser)]
+    struct TestSubcommandArgs {
+        #[clap(subcommand)]
+        command: TestCommand,
+        #[clap(flatten)]
+        flags: Flags,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum TestCommand {
+        Build,
+    }
+
+    fn generate_from(args: &[&str]) -> Option<String> {
+        generate_from_command(TestArgs::command(), args)
+    }
+
+    fn generate_from_command(command: Command, args: &[&str]) -> Option<String> {
+        let mut command = relax_required(command);
+        let matches = command.try_get_matches_from_mut(args).unwrap();
+        let flags = Flags::from_arg_matches(&matches).unwrap();
+        let out = generate(&flags, &mut command, "test".to_string())?;
+        Some(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_generate() {
+        let out = generate_from(&["test", "--generate-completion", "bash"]).unwrap();
+        assert!(out.contains("complete -F _test"), "{out}");
+        let out = generate_from(&["test", "--generate-completion=powershell"]).unwrap();
+        assert!(out.contains("Register-ArgumentCompleter"), "{out}");
+        let out = generate_from(&["test", "--generate-man"]).unwrap();
+        assert!(out.contains(".TH test"), "{out}");
+        assert_eq!(generate_from(&["test", "foo"]), None);
+        // after `--`, these are values and not options
+        assert_eq!(generate_from(&["test", "--", "--generate-man"]), None);
+        assert_eq!(
+            generate_from(&["test", "--", "--generate-completion=bash"]),
+            None
+        );
+        // the subcommand is required when parsing normally
+        let command = || TestSubcommandArgs::command().mut_args(|arg| arg.global(true));
+        assert!(command().try_get_matches_from(["test"]).is_err());
+        let out = generate_from_command(command(), &["test", "--generate-man"]).unwrap();
+        assert!(out.contains(".TH test"), "{out}");
+        let out =
+            generate_from_command(command(), &["test", "--generate-completion", "bash"]).unwrap();
+        assert!(out.contains("complete -F _test"), "{out}");
+        assert_eq!(generate_from_command(command(), &["test", "build"]), None);
+    }
+
+    #[test]
+    fn test_error_report() {
+        let result: Result<(), std::io::Error> = Err(std::io::Error::other("disk full"));
+        let line = line!() + 2;
+        let error = anyhow::Context::context(result, "failed to write")
+            .hint("free up some space")
+            .hint("or use a different disk")
+            .unwrap_err()
+            .context("failed to build");
+        let capture = crate::cli::capture();
+        print_error_report(&error);
+        let messages = capture.messages();
+        assert_eq!(messages.len(), 3, "{messages:?}");
+        let (lv, report) = &messages[0];
+        assert_eq!(*lv, lv::E);
+        let expected = format!(
+            "fatal: failed to build\ncaused by:\n  1: failed to write\n  2: disk full\nhint attached at {}:{line}",
+            file!()
+        );
+        assert!(report.starts_with(&expected), "{report}");
+        assert_eq!(messages[1], (lv::H, "free up some space".to_string()));
+        assert_eq!(messages[2], (lv::H, "or use a different disk".to_string()));
+    }
+}
@@ -1,50 +1,113 @@
-/// Color code definition
+use std::sync::Mutex;
+
+use crate::cli::theme::{ColorSupport, LevelStyle, Theme, ThemeColor};
+use crate::lv;
+
+/// Color codes and symbols resolved from the [`Theme`]
 #[derive(Clone, Copy)]
 pub(crate) struct Colors {
     pub reset: &'static str,
-    pub yellow: &'static str,
-    pub red: &'static str,
-    pub gray: &'static str,
-    pub magenta: &'static str,
-    pub cyan: &'static str,
-    pub green: &'static str,
+    pub decoration: &'static str,
+    pub thread_name: &'static str,
+    pub progress: &'static str,
+    pub progress_done: &'static str,
+    pub progress_interrupted: &'static str,
+    pub tree: &'static str,
+    pub error: LevelColors,
+    pub hint: LevelColors,
+    pub print: LevelColors,
+    pub warn: LevelColors,
+    pub info: LevelColors,
+    pub debug: LevelColors,
+    pub trace: LevelColors,
+    #[cfg(feature = "prompt")]
+    pub prompt: LevelColors,
 }
 
-static NOCOLOR: Colors = Colors {
-    reset: "",
-    yellow: "",
-    red: "",
-    gray: "",
-    magenta: "",
-    cyan: "",
-    green: "",
-};
-
-static COLOR: Colors = Colors {
-    reset: "\x1b[0m",
-    yellow: "\x1b[93m",
-    red: "\x1b[91m",
-    gray: "\x1b[90m",
-    magenta: "\x1b[95m",
-    cyan: "\x1b[96m",
-    green: "\x1b[92m",
-};
-
-#[inline]
-pub(crate) const fn colors(use_color: bool) -> Colors {
-    if use_color { COLOR } else { NOCOLOR }
+/// Resolved [`LevelStyle`]
+#[derive(Clone, Copy)]
+pub(crate) struct LevelColors {
+    pub symbol: &'static str,
+    pub symbol_color: &'static str,
+    pub separator: &'static str,
+    pub separator_color: &'static str,
+    pub text: &'static str,
+}
+
+impl Colors {
+    /// Resolve the theme. If `support` is `None`, color is disabled
+    pub fn new(theme: &Theme, support: Option<ColorSupport>) -> Self {
+        let code = |color: ThemeColor| match support {
+            Some(support) => intern(color.to_ansi(support)),
+            None => "",
+        };
+        let level = |style: &LevelStyle| LevelColors {
+            symbol: style.symbol,
+            symbol_color: code(style.symbol_color),
+            separator: style.separator,
+            separator_color: code(style.separator_color),
+            text: code(style.text_color),
+        };
+        Self {
+            reset: if support.is_some() { "\x1b[0m" } else { "" },
+            decoration: code(theme.decoration),
+            thread_name: code(theme.thread_name),
+            progress: code(theme.progress),
+            progress_done: code(theme.progress_done),
+            progress_interrupted: code(theme.progress_interrupted),
+            tree: code(theme.tree),
+            error: level(&theme.error),
+            hint: level(&theme.hint),
+            print: level(&theme.print),
+            warn: level(&theme.warn),
+            info: level(&theme.info),
+            debug: level(&theme.debug),
+            trace: level(&theme.trace),
+            #[cfg(feature = "prompt")]
+            prompt: level(&theme.prompt),
+        }
+    }
+
+    /// Get the colors for a message level
+    pub fn level(&self, lv: lv::Lv) -> Option<&LevelColors> {
+        Some(match lv {
+            lv::Lv::Off => return None,
+            lv::Lv::Error => &self.error,
+            lv::Lv::Hint => &self.hint,
+            lv::Lv::Print => &self.print,
+            lv::Lv::Warn => &self.warn,
+            lv::Lv::Info => &self.info,
+            lv::Lv::Debug => &self.debug,
+            lv::Lv::Trace => &self.trace,
+        })
+    }
+}
+
+/// Get a static reference to the escape code, since the
+/// formatting code keeps static references to the colors.
+/// The theme can be loaded multiple times (for example in tests),
+/// so the codes are deduplicated to not leak more than the number of distinct colors
+fn intern(code: String) -> &'static str {
+    static CODES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+    let mut codes = CODES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(x) = codes.iter().find(|x| **x == code) {
+        return x;
+    }
+    let code: &'static str = Box::leak(code.into_boxed_str());
+    codes.push(code);
+    code
 }
 
 /// Iterator of (char, width)
 pub(crate) fn with_width(x: std::str::Chars<'_>) -> AnsiWidthIter<'_> {
     AnsiWidthIter {
-        is_escaping: false,
+        escape: EscapeState::default(),
         chars: x,
     }
 }
 
 pub(crate) struct AnsiWidthIter<'a> {
-    is_escaping: bool,
+    escape: EscapeState,
     chars: std::str::Chars<'a>,
 }
 
@@ -53,13 +116,7 @@ impl<'a> Iterator for AnsiWidthIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let c = self.chars.next()?;
-        let width = if self.is_escaping {
-            if is_esc_end(c) {
-                self.is_escaping = false;
-            }
-            0
-        } else if c == '\x1b' {
-            self.is_escaping = true;
+        let width = if self.escape.update(c) {
             0
         } else {
             use unicode_width::UnicodeWidthChar;
@@ -70,7 +127,94 @@ impl<'a> Iterator for AnsiWidthIter<'a> {
     }
 }
 
+/// State for skipping escape sequences in text. This handles
+/// CSI sequences (such as colors) and OSC sequences (such as hyperlinks)
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EscapeState {
+    #[default]
+    None,
+    /// After ESC
+    Escape,
+    /// Inside an OSC sequence, which ends with BEL or ST (`ESC \`)
+    Osc,
+    /// After ESC inside an OSC sequence
+    OscEscape,
+}
+impl EscapeState {
+    /// Update the state with the next char, and return if the char
+    /// is part of an escape sequence
+    pub fn update(&mut self, c: char) -> bool {
+        match self {
+            Self::None => {
+                if c != '\x1b' {
+                    return false;
+                }
+                *self = Self::Escape;
+            }
+            Self::Escape => {
+                if c == ']' {
+                    *self = Self::Osc;
+                } else if c == '\\' || is_esc_end(c) {
+                    *self = Self::None;
+                }
+            }
+            Self::Osc => match c {
+                '\x07' => *self = Self::None,
+                '\x1b' => *self = Self::OscEscape,
+                _ => {}
+            },
+            Self::OscEscape => *self = Self::None,
+        }
+        true
+    }
+}
+
+/// Get the display width of the string, not counting escape sequences
+pub(crate) fn width(x: &str) -> usize {
+    with_width(x.chars()).map(|(_, w)| w).sum()
+}
+
 pub(crate) fn is_esc_end(c: char) -> bool {
     // we only do very basic check right now
     c < u8::MAX as char && b"mAKGJBCDEFHSTfhlin".contains(&(c as u8))
 }
+
+/// Push the string to the output, with escape sequences removed
+pub(crate) fn push_stripped(out: &mut String, x: &str) {
+    let mut escape = EscapeState::default();
+    for c in x.chars() {
+        if !escape.update(c) {
+            out.push(c);
+        }
+    }
+}
+
+/// Push the text truncated to `max_width`. If truncated, return the width remaining
+/// that needs to be padded
+pub(crate) fn push_truncated(out: &mut String, x: &str, max_width: usize) -> usize {
+    if width(x) <= max_width {
+        out.push_str(x);
+        return 0;
+    }
+    // leave space for the ellipsis
+    let mut remaining = max_width.saturating_sub(1);
+    let mut is_truncated = false;
+    for (c, w) in with_width(x.chars()) {
+        if w == 0 {
+            // keep escape sequences so styles are still closed
+            out.push(c);
+            continue;
+        }
+        if is_truncated {
+            continue;
+        }
+        if w > remaining {
+            is_truncated = true;
+            out.push(super::glyphs().ellipsis);
+            continue;
+        }
+        remaining -= w;
+        out.push(c);
+    }
+    remaining
+}
@@ -1,4 +1,5 @@
 use crate::cli::fmt::{self, ansi};
+use crate::style::FG_RESET;
 
 /// Buffer for formatting printing messages
 pub(crate) struct FormatBuffer {
@@ -12,6 +13,15 @@ pub(crate) struct FormatBuffer {
     gray_color: &'static str,
     /// ANSI code for the current text color
     text_color: &'static str,
+    /// Number of spaces to indent continuation lines with
+    indent: usize,
+    /// State for tracking escape sequences in the content
+    escape: ansi::EscapeState,
+    /// The escape sequence being pushed
+    sequence: String,
+    /// The sequence that started the current hyperlink, which
+    /// is closed before and re-opened after wrapping
+    link: Option<String>,
 }
 
 impl FormatBuffer {
@@ -22,6 +32,10 @@ impl FormatBuffer {
             buffer: String::new(),
             gray_color: "",
             text_color: "",
+            indent: 0,
+            escape: ansi::EscapeState::None,
+            sequence: String::new(),
+            link: None,
         }
     }
     /// Get the formatted buffer content
@@ -40,6 +54,19 @@ impl FormatBuffer {
         self.width = fmt::term_width_or_max();
         self.gray_color = gray_color;
         self.text_color = text_color;
+        self.indent = 0;
+        self.escape = ansi::EscapeState::None;
+        self.sequence.clear();
+        self.link = None;
+    }
+    /// Push indentation for the scope depth. Continuation lines
+    /// will also be indented
+    pub fn push_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.push(' ', 1);
+            self.push(' ', 1);
+        }
+        self.indent += depth * 2;
     }
 
     /// Push a newline character (note this is different from [`new_line`](Self::new_line))
@@ -51,8 +78,30 @@ impl FormatBuffer {
     pub fn push_control(&mut self, x: &str) {
         self.buffer.push_str(x)
     }
+    /// Push the symbol and separator of a message prefix
+    pub fn push_prefix(&mut self, style: &ansi::LevelColors) {
+        self.push_control(style.symbol_color);
+        self.push_str(style.symbol);
+        if style.separator_color != style.symbol_color {
+            self.push_control(style.separator_color);
+        }
+        self.push_str(style.separator);
+    }
     /// Push and format string content
+    ///
+    /// The text color is restored after styled spans that reset the foreground color
     pub fn push_str(&mut self, x: &str) {
+        let mut parts = x.split(FG_RESET);
+        if let Some(part) = parts.next() {
+            self.push_str_part(part);
+        }
+        for part in parts {
+            self.buffer.push_str(FG_RESET);
+            self.buffer.push_str(self.text_color);
+            self.push_str_part(part);
+        }
+    }
+    fn push_str_part(&mut self, x: &str) {
         for (c, w) in ansi::with_width(x.chars()) {
             self.push(c, w);
         }
@@ -63,6 +112,10 @@ impl FormatBuffer {
             self.new_line();
             return;
         }
+        if self.escape.update(c) {
+            self.push_escape(c);
+            return;
+        }
         if self.width < 5 {
             // give up
             self.buffer.push(c);
@@ -74,12 +127,62 @@ impl FormatBuffer {
         self.buffer.push(c);
         self.curr += w;
     }
+    /// Push a char that is part of an escape sequence, and keep track of hyperlinks
+    fn push_escape(&mut self, c: char) {
+        self.buffer.push(c);
+        self.sequence.push(c);
+        if self.escape != ansi::EscapeState::None {
+            return;
+        }
+        if let Some(is_start) = fmt::osc::is_link_start(&self.sequence) {
+            self.link = is_start.then(|| self.sequence.clone());
+        }
+        self.sequence.clear();
+    }
     /// Start formatting a new line
     pub fn new_line(&mut self) {
+        // the gutter should not be part of the link
+        if self.link.is_some() {
+            self.buffer.push_str(fmt::osc::LINK_END);
+        }
         self.buffer.push('\n');
         self.buffer.push_str(self.gray_color);
         self.buffer.push_str(" | ");
         self.buffer.push_str(self.text_color);
-        self.curr = 3;
+        for _ in 0..self.indent {
+            self.buffer.push(' ');
+        }
+        self.curr = 3 + self.indent;
+        if let Some(link) = &self.link {
+            self.buffer.push_str(link);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_text_color() {
+        let mut buffer = FormatBuffer::new();
+        buffer.reset("", "\x1b[93m");
+        buffer.push_str("a\x1b[1;91mb\x1b[22m\x1b[39mc");
+        assert_eq!(buffer.as_str(), "a\x1b[1;91mb\x1b[22m\x1b[39m\x1b[93mc");
+        assert_eq!(buffer.curr, 3);
+    }
+
+    #[test]
+    fn test_wrap_link() {
+        let mut buffer = FormatBuffer::new();
+        buffer.reset("", "");
+        buffer.width = 8;
+        let mut link = String::new();
+        fmt::osc::push_link(&mut link, "file:///a", "abcdefghij");
+        buffer.push_str(&link);
+        assert_eq!(
+            buffer.as_str(),
+            "\x1b]8;;file:///a\x1b\\abcdefgh\x1b]8;;\x1b\\\n | \x1b]8;;file:///a\x1b\\ij\x1b]8;;\x1b\\"
+        );
     }
 }
@@ -0,0 +1,30 @@
+/// Push the message part of a GitHub Actions workflow command,
+/// with escape sequences (ANSI codes) removed
+///
+/// See <https://docs.github.com/en/actions/reference/workflows-and-actions/workflow-commands>
+pub(crate) fn push_workflow_data(out: &mut String, x: &str) {
+    push_workflow_escaped(out, x, false)
+}
+
+/// Push a property value of a GitHub Actions workflow command,
+/// with escape sequences (ANSI codes) removed
+pub(crate) fn push_workflow_property(out: &mut String, x: &str) {
+    push_workflow_escaped(out, x, true)
+}
+
+fn push_workflow_escaped(out: &mut String, x: &str, is_property: bool) {
+    let mut escape = super::ansi::EscapeState::default();
+    for c in x.chars() {
+        if escape.update(c) {
+            continue;
+        }
+        match c {
+            '%' => out.push_str("%25"),
+            '\r' => out.push_str("%0D"),
+            '\n' => out.push_str("%0A"),
+            ':' if is_property => out.push_str("%3A"),
+            ',' if is_property => out.push_str("%2C"),
+            c => out.push(c),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Box characters for drawing trees and tables, and other glyphs
+//! that have an ASCII fallback
+
+use crate::lv;
+
+/// Glyphs for the charset, see [`cu::lv::Charset`](crate::lv::Charset)
+pub(crate) struct Glyphs {
+    /// `|>`, for a child with more siblings after it
+    pub bar_tick: char,
+    /// `|`, for continuing the hierarchy
+    pub bar: char,
+    /// `>`, for the last child
+    pub tick: char,
+    /// `-`, for the line under table headers
+    pub line: char,
+    /// Replaces the truncated part of a text
+    pub ellipsis: char,
+    /// Symbol for progress bar messages that are not animated
+    pub progress: &'static str,
+    /// Frames of the progress bar spinner
+    pub spinner: &'static [char],
+    /// Filled part of the graphical bar in progress templates
+    pub bar_filled: char,
+    /// Empty part of the graphical bar in progress templates
+    pub bar_empty: char,
+}
+
+const UNICODE: Glyphs = Glyphs {
+    bar_tick: '\u{251C}',
+    bar: '\u{2502}',
+    tick: '\u{2514}',
+    line: '\u{2500}',
+    ellipsis: '\u{2026}',
+    progress: "\u{283f}",
+    spinner: &[
+        '\u{280b}', '\u{2819}', '\u{2838}', '\u{2834}', '\u{2826}', '\u{2807}',
+    ],
+    bar_filled: '\u{2588}',
+    bar_empty: '\u{2591}',
+};
+
+const ASCII: Glyphs = Glyphs {
+    bar_tick: '+',
+    bar: '|',
+    tick: '`',
+    line: '-',
+    ellipsis: '~',
+    progress: "*",
+    spinner: &['|', '/', '-', '\\'],
+    bar_filled: '#',
+    bar_empty: '-',
+};
+
+/// Get the glyphs for the current charset
+pub(crate) fn glyphs() -> &'static Glyphs {
+    if lv::unicode_enabled() {
+        &UNICODE
+    } else {
+        &ASCII
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_glyphs() {
+        let chars = [
+            ASCII.bar_tick,
+            ASCII.bar,
+            ASCII.tick,
+            ASCII.line,
+            ASCII.ellipsis,
+            ASCII.bar_filled,
+            ASCII.bar_empty,
+        ];
+        assert!(chars.iter().all(char::is_ascii));
+        assert!(ASCII.progress.is_ascii());
+        assert!(ASCII.spinner.iter().all(char::is_ascii));
+        // the spinner frames need to have the same width
+        assert_eq!(ASCII.spinner.len(), 4);
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::SystemTime;
+
+use crate::cli::fmt;
+
+/// Helper to write one JSON object (a record) on a single line
+pub(crate) struct JsonRecord<'a> {
+    out: &'a mut String,
+}
+
+impl<'a> JsonRecord<'a> {
+    /// Start a record with the `type` and `timestamp` fields
+    pub fn new(out: &'a mut String, ty: &str) -> Self {
+        out.push_str("{\"type\":");
+        push_json_str(out, ty);
+        out.push_str(",\"timestamp\":\"");
+        fmt::push_timestamp(out, SystemTime::now());
+        out.push('"');
+        Self { out }
+    }
+    /// Add a string field. Escape sequences in the value are removed
+    pub fn str(mut self, key: &str, value: &str) -> Self {
+        self.key(key);
+        push_json_str(self.out, value);
+        self
+    }
+    /// Add a string field, or `null`
+    pub fn opt_str(mut self, key: &str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.str(key, value),
+            None => {
+                self.key(key);
+                self.out.push_str("null");
+                self
+            }
+        }
+    }
+    /// Add a number field, or `null`
+    pub fn opt_u32(mut self, key: &str, value: Option<u32>) -> Self {
+        self.key(key);
+        match value {
+            Some(value) => {
+                use std::fmt::Write as _;
+                let _ = write!(self.out, "{value}");
+            }
+            None => self.out.push_str("null"),
+        }
+        self
+    }
+    /// Add a field with a value that is already JSON
+    pub fn raw(mut self, key: &str, value: &str) -> Self {
+        self.key(key);
+        self.out.push_str(value);
+        self
+    }
+    /// End the record and the line
+    pub fn finish(self) {
+        self.out.push_str("}\n");
+    }
+    fn key(&mut self, key: &str) {
+        self.out.push(',');
+        push_json_str(self.out, key);
+        self.out.push(':');
+    }
+}
+
+/// Push a JSON string literal with quotes, with escape sequences
+/// (ANSI codes) removed
+pub(crate) fn push_json_str(out: &mut String, x: &str) {
+    use std::fmt::Write as _;
+    out.push('"');
+    let mut escape = fmt::ansi::EscapeState::default();
+    for c in x.chars() {
+        if escape.update(c) {
+            continue;
+        }
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        let mut out = String::new();
+        push_json_str(&mut out, "a\"b\\c\nd\x01\x1b[31mred\x1b[0m");
+        assert_eq!(out, r#""a\"b\\c\nd\u0001red""#);
+        out.clear();
+        push_json_str(
+            &mut out,
+            "see \x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\ or \x1b]8;;file:///b\x07b\x1b]8;;\x07",
+        );
+        assert_eq!(out, r#""see a or b""#);
+    }
+}
@@ -5,3 +5,12 @@ mod term_size;
 pub use term_size::*;
 mod format_buffer;
 pub(crate) use format_buffer::*;
+mod timestamp;
+pub(crate) use timestamp::*;
+mod json;
+pub(crate) use json::*;
+mod github;
+pub(crate) use github::*;
+mod glyph;
+pub(crate) use glyph::*;
+pub(crate) mod osc;
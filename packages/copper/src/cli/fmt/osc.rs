@@ -0,0 +1,96 @@
+//! OSC (Operating System Command) sequences for terminal integrations
+
+use std::fmt::Write as _;
+
+/// Show the progress in the taskbar or tab (OSC 9;4), `None` for indeterminate
+pub(crate) fn push_progress(out: &mut String, percentage: Option<u8>) {
+    // _: fmt for string does not fail
+    let _ = match percentage {
+        Some(x) => write!(out, "\x1b]9;4;1;{x}\x07"),
+        None => write!(out, "\x1b]9;4;3;0\x07"),
+    };
+}
+
+/// Remove the progress from the taskbar or tab
+pub(crate) fn push_progress_clear(out: &mut String) {
+    out.push_str("\x1b]9;4;0;0\x07");
+}
+
+/// Save the current window title, so it can be restored later
+pub(crate) fn push_title_save(out: &mut String) {
+    out.push_str("\x1b[22;2t");
+}
+
+/// Restore the saved window title
+pub(crate) fn push_title_restore(out: &mut String) {
+    out.push_str("\x1b[23;2t");
+}
+
+/// Set the window title (OSC 2), with control characters removed
+pub(crate) fn push_title(out: &mut String, title: &str) {
+    out.push_str("\x1b]2;");
+    let mut escape = super::ansi::EscapeState::default();
+    for c in title.chars() {
+        if !escape.update(c) && !c.is_control() {
+            out.push(c);
+        }
+    }
+    out.push('\x07');
+}
+
+/// End of a hyperlink (OSC 8 with an empty URL)
+pub(crate) const LINK_END: &str = "\x1b]8;;\x1b\\";
+
+/// Push a hyperlink (OSC 8) with the display text
+pub(crate) fn push_link(out: &mut String, url: &str, text: &str) {
+    let _ = write!(out, "\x1b]8;;{url}\x1b\\{text}{LINK_END}");
+}
+
+/// Check if the escape sequence starts a hyperlink. Returns `None`
+/// if it's not a hyperlink sequence, and `Some(false)` if it ends a hyperlink
+pub(crate) fn is_link_start(sequence: &str) -> Option<bool> {
+    let params = sequence.strip_prefix("\x1b]8;")?;
+    let (_, url) = params.split_once(';')?;
+    let url = url.trim_end_matches(['\x07', '\x1b', '\\']);
+    Some(!url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let mut out = String::new();
+        push_progress(&mut out, Some(42));
+        assert_eq!(out, "\x1b]9;4;1;42\x07");
+        out.clear();
+        push_progress(&mut out, None);
+        assert_eq!(out, "\x1b]9;4;3;0\x07");
+        out.clear();
+        push_progress_clear(&mut out);
+        assert_eq!(out, "\x1b]9;4;0;0\x07");
+    }
+
+    #[test]
+    fn test_title() {
+        let mut out = String::new();
+        push_title_save(&mut out);
+        push_title(&mut out, "\x1b[1;32mbuilding\x1b[0m: foo\x07\nbar");
+        push_title_restore(&mut out);
+        assert_eq!(out, "\x1b[22;2t\x1b]2;building: foobar\x07\x1b[23;2t");
+    }
+
+    #[test]
+    fn test_link() {
+        let mut out = String::new();
+        push_link(&mut out, "file:///tmp/a.txt", "a.txt");
+        assert_eq!(out, "\x1b]8;;file:///tmp/a.txt\x1b\\a.txt\x1b]8;;\x1b\\");
+        assert_eq!(is_link_start("\x1b]8;;file:///tmp/a.txt\x1b\\"), Some(true));
+        assert_eq!(is_link_start("\x1b]8;id=1;https://a.b\x07"), Some(true));
+        assert_eq!(is_link_start(LINK_END), Some(false));
+        assert_eq!(is_link_start("\x1b]8;;\x07"), Some(false));
+        assert_eq!(is_link_start("\x1b]2;title\x07"), None);
+        assert_eq!(is_link_start("\x1b[31m"), None);
+    }
+}
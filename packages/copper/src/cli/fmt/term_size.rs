@@ -1,3 +1,8 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set when the terminal is resized
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
 /// Get the terminal width, or the internal max if cannot get
 pub fn term_width_or_max() -> usize {
     term_width().unwrap_or(400)
@@ -18,3 +23,70 @@ pub fn term_width_height() -> Option<(usize, usize)> {
         terminal_size().map(|(Width(w), Height(h))| ((w as usize).min(400), (h as usize).min(400)))
     }
 }
+
+/// Start listening for the terminal resize events (`SIGWINCH`).
+///
+/// Only supported on unix, other platforms need to detect the
+/// resize by comparing the width
+pub fn listen_for_resize() {
+    #[cfg(unix)]
+    {
+        static ONCE: std::sync::Once = std::sync::Once::new();
+        ONCE.call_once(sigwinch::install);
+    }
+}
+
+/// Check if the terminal is resized since the last check
+pub fn take_resized() -> bool {
+    RESIZED.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+mod sigwinch {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use libc::{c_int, c_void, siginfo_t};
+
+    /// The handler installed before ours, which is called after ours
+    static PREVIOUS: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+    /// If the previous handler takes the signal info
+    static PREVIOUS_SIGINFO: AtomicBool = AtomicBool::new(false);
+
+    type Handler = extern "C" fn(c_int);
+    type SigInfoHandler = extern "C" fn(c_int, *mut siginfo_t, *mut c_void);
+
+    pub fn install() {
+        // SAFETY: the structs are plain C data, and the handler only
+        // does async-signal-safe operations
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as SigInfoHandler as usize;
+            // restart the syscalls, so reading prompts is not interrupted
+            action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            if libc::sigaction(libc::SIGWINCH, &action, &mut previous) == 0 {
+                PREVIOUS_SIGINFO
+                    .store(previous.sa_flags & libc::SA_SIGINFO != 0, Ordering::Relaxed);
+                PREVIOUS.store(previous.sa_sigaction, Ordering::Release);
+            }
+        }
+    }
+
+    extern "C" fn handler(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+        super::RESIZED.store(true, Ordering::Relaxed);
+        let previous = PREVIOUS.load(Ordering::Acquire);
+        if previous == libc::SIG_DFL || previous == libc::SIG_IGN {
+            return;
+        }
+        // SAFETY: the previous handler was installed with the signature
+        // indicated by the flags
+        unsafe {
+            if PREVIOUS_SIGINFO.load(Ordering::Relaxed) {
+                std::mem::transmute::<usize, SigInfoHandler>(previous)(signal, info, context);
+            } else {
+                std::mem::transmute::<usize, Handler>(previous)(signal);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Format the time as a UTC timestamp like `2025-01-31T12:34:56.789Z`
+pub(crate) fn push_timestamp(out: &mut String, time: SystemTime) {
+    use std::fmt::Write as _;
+    let millis = match time.duration_since(UNIX_EPOCH) {
+        Ok(x) => x.as_millis() as u64,
+        Err(_) => 0,
+    };
+    let secs = millis / 1000;
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let secs_of_day = secs % 86400;
+    let _ = write!(
+        out,
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        millis % 1000
+    );
+}
+
+/// Convert days since UNIX epoch to (year, month, day)
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn format(millis: u64) -> String {
+        let mut out = String::new();
+        push_timestamp(&mut out, UNIX_EPOCH + Duration::from_millis(millis));
+        out
+    }
+
+    #[test]
+    fn test_epoch() {
+        assert_eq!(format(0), "1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_leap_day() {
+        assert_eq!(format(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(format(1_709_251_199_999), "2024-02-29T23:59:59.999Z");
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        assert_eq!(format(1_735_648_496_789), "2024-12-31T12:34:56.789Z");
+    }
+}
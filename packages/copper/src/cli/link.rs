@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::fmt;
+
+/// # Hyperlinks
+/// Display a path as a clickable link in terminals that support
+/// OSC 8 hyperlinks (for example, Windows Terminal, WezTerm, iTerm2 and
+/// most VTE-based terminals).
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// cu::info!("report written to {}", cu::link("target/report.html"));
+/// ```
+///
+/// The link points to the absolute path of the file. The displayed
+/// text is the path as given. When colors are disabled (for example,
+/// when the output is not a terminal), only the path is displayed,
+/// so the output is the same as `path.display()`.
+pub fn link(path: impl AsRef<Path>) -> Link {
+    Link {
+        path: path.as_ref().to_path_buf(),
+    }
+}
+
+/// A path displayed as a hyperlink. See [`link`](fn@crate::link)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    path: PathBuf,
+}
+
+impl Link {
+    /// Get the `file://` URL of the link
+    pub fn url(&self) -> String {
+        let path = std::path::absolute(&self.path).unwrap_or_else(|_| self.path.clone());
+        let path = path.to_string_lossy();
+        let path = path.replace('\\', "/");
+        let mut url = String::from("file://");
+        if !path.starts_with('/') {
+            // windows paths like C:/foo
+            url.push('/');
+        }
+        for b in path.bytes() {
+            match b {
+                b'A'..=b'Z'
+                | b'a'..=b'z'
+                | b'0'..=b'9'
+                | b'-'
+                | b'.'
+                | b'_'
+                | b'~'
+                | b'/'
+                | b':' => url.push(b as char),
+                _ => {
+                    use std::fmt::Write as _;
+                    let _ = write!(url, "%{b:02X}");
+                }
+            }
+        }
+        url
+    }
+}
+
+impl std::fmt::Display for Link {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !crate::lv::color_enabled() {
+            return self.path.display().fmt(f);
+        }
+        let mut out = String::new();
+        fmt::osc::push_link(&mut out, &self.url(), &self.path.display().to_string());
+        f.write_str(&out)
+    }
+}
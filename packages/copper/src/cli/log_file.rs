@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use crate::cli::fmt::{self, ansi};
+use crate::cli::progress::BarResult;
+use crate::cli::{THREAD_NAME, scope};
+use crate::lv;
+
+/// If a log file is currently active. Checked before formatting messages
+/// that are not visible in the console, to avoid the cost when there is no log file
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Check if messages should be formatted for the log file,
+/// regardless of the console print level
+#[inline(always)]
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+#[inline(always)]
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Release)
+}
+
+/// File to tee all printed messages to, in plain text (no colors)
+/// with timestamps
+pub(crate) struct LogFile {
+    file: File,
+    buffer: String,
+}
+impl LogFile {
+    /// Create the log file. Existing file will be truncated
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            file,
+            buffer: String::new(),
+        })
+    }
+
+    /// Write a message. Every line is prefixed with the time, and
+    /// continuation lines are indented like in the console
+    pub fn write_message(&mut self, lv: lv::Lv, message: &str) {
+        let prefix = match lv {
+            lv::Lv::Off => return,
+            lv::Lv::Error => "E]",
+            lv::Lv::Hint => "H]",
+            lv::Lv::Print => "::",
+            lv::Lv::Warn => "W]",
+            lv::Lv::Info => "I]",
+            lv::Lv::Debug => "D]",
+            lv::Lv::Trace => "*]",
+        };
+        self.buffer.clear();
+        let now = SystemTime::now();
+        fmt::push_timestamp(&mut self.buffer, now);
+        self.buffer.push(' ');
+        self.buffer.push_str(prefix);
+        THREAD_NAME.with_borrow(|x| {
+            if let Some(x) = x {
+                self.buffer.push('[');
+                self.buffer.push_str(x);
+                self.buffer.push(']');
+            }
+        });
+        let indent = scope::depth() * 2;
+        let mut lines = message.lines();
+        if let Some(line) = lines.next() {
+            self.buffer.push(' ');
+            push_indent(&mut self.buffer, indent);
+            ansi::push_stripped(&mut self.buffer, line);
+        }
+        for line in lines {
+            self.buffer.push('\n');
+            fmt::push_timestamp(&mut self.buffer, now);
+            self.buffer.push_str("  | ");
+            push_indent(&mut self.buffer, indent);
+            ansi::push_stripped(&mut self.buffer, line);
+        }
+        self.buffer.push('\n');
+        self.flush_buffer();
+    }
+
+    /// Write the final message of a progress bar
+    pub fn write_bar_done(&mut self, result: &BarResult) {
+        let message = match result {
+            BarResult::DontKeep => return,
+            BarResult::Done(message) => message,
+            BarResult::Interrupted(message) => message,
+        };
+        self.buffer.clear();
+        fmt::push_timestamp(&mut self.buffer, SystemTime::now());
+        self.buffer.push(' ');
+        self.buffer.push_str(fmt::glyphs().progress);
+        self.buffer.push(']');
+        if !message.starts_with('[') {
+            self.buffer.push(' ');
+        }
+        ansi::push_stripped(&mut self.buffer, message);
+        self.buffer.push('\n');
+        self.flush_buffer();
+    }
+
+    fn flush_buffer(&mut self) {
+        // errors are ignored, since there is nowhere to report them
+        let _ = self.file.write_all(self.buffer.as_bytes());
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push(' ');
+    }
+}
@@ -1,4 +1,7 @@
-use cu::cli::printer::PRINTER;
+#[cfg(all(feature = "process", feature = "json"))]
+use cu::cli::printer::Annotation;
+use cu::cli::printer::{Block, PRINTER, Source};
+use cu::cli::{log_file, summary};
 use cu::lv;
 /// Print something
 ///
@@ -19,6 +22,39 @@ macro_rules! hint {
     }}
 }
 
+/// Print data to stdout, followed by a new line
+///
+/// Unlike [`print!`](macro@crate::print), the data is printed as-is, without prefix,
+/// colors or wrapping, and is not affected by the verbosity. Use this for the
+/// result of the program, such as a list that will be piped to another program.
+///
+/// Once this is used, messages and progress bars are printed to stderr
+/// to not mix with the data. Messages are also printed to stderr if stdout is not terminal.
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// cu::info!("listing items"); // printed to stderr when piped
+/// for item in ["a", "b"] {
+///     cu::output!("{item}");
+/// }
+/// ```
+#[macro_export]
+#[cfg(feature = "print")]
+macro_rules! output {
+    ($($fmt_args:tt)*) => {{
+        $crate::cli::__output(format_args!($($fmt_args)*));
+    }}
+}
+
+/// Start a [scope](fn@crate::cli::scope) with a formatted header
+#[macro_export]
+#[cfg(feature = "print")]
+macro_rules! scope {
+    ($($fmt_args:tt)*) => {{
+        $crate::cli::scope(format!($($fmt_args)*))
+    }}
+}
+
 /// Show prompt to the user. See [Prompting](fn@crate::prompt)
 #[cfg(all(feature = "prompt", not(feature = "coroutine")))]
 #[macro_export]
@@ -63,14 +99,89 @@ macro_rules! yesno {
 
 /// Internal print function for macros
 #[doc(hidden)]
+#[track_caller]
 pub fn __print_with_level(lv: lv::Lv, message: std::fmt::Arguments<'_>) {
-    if !lv.can_print(lv::PRINT_LEVEL.get()) {
+    let to_console = lv.can_print(lv::PRINT_LEVEL.get());
+    let to_log_file = log_file::is_enabled();
+    if !to_console && !to_log_file {
         return;
     }
     let message = format!("{message}");
+    if to_console {
+        summary::record(lv, &message);
+    }
+    let location = std::panic::Location::caller();
+    let source = Source {
+        file: Some(location.file()),
+        line: Some(location.line()),
+        ..Default::default()
+    };
     if let Ok(mut printer) = PRINTER.lock() {
         if let Some(printer) = printer.as_mut() {
-            printer.print_message(lv, &message);
+            if to_log_file {
+                printer.print_message_to_log_file(lv, &message);
+            }
+            if to_console {
+                printer.print_message(lv, &message, &source);
+            }
         }
     }
 }
+
+/// Internal output function for macros
+#[doc(hidden)]
+pub fn __output(data: std::fmt::Arguments<'_>) {
+    let text = format!("{data}\n");
+    if let Ok(mut printer) = PRINTER.lock()
+        && let Some(printer) = printer.as_mut()
+    {
+        printer.print_output(&text);
+        return;
+    }
+    use std::io::Write as _;
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "{text}");
+    let _ = stdout.flush();
+}
+
+/// Print a message that refers to a location in a file. The location
+/// is only displayed in formats that support annotations
+#[cfg(all(feature = "process", feature = "json"))]
+pub(crate) fn print_annotated(lv: lv::Lv, message: &str, annotation: &Annotation) {
+    let to_console = lv.can_print(lv::PRINT_LEVEL.get());
+    let to_log_file = log_file::is_enabled();
+    if !to_console && !to_log_file {
+        return;
+    }
+    if to_console {
+        summary::record(lv, message);
+    }
+    let Ok(mut printer) = PRINTER.lock() else {
+        return;
+    };
+    let Some(printer) = printer.as_mut() else {
+        return;
+    };
+    if to_log_file {
+        printer.print_message_to_log_file(lv, message);
+    }
+    if to_console {
+        printer.print_annotated(lv, message, annotation);
+    }
+}
+
+/// Print a block of preformatted text, such as a table
+pub(crate) fn print_block(lv: lv::Lv, block: &dyn Block) {
+    let to_console = lv.can_print(lv::PRINT_LEVEL.get());
+    let to_log_file = log_file::is_enabled();
+    if !to_console && !to_log_file {
+        return;
+    }
+    let Ok(mut printer) = PRINTER.lock() else {
+        return;
+    };
+    let Some(printer) = printer.as_mut() else {
+        return;
+    };
+    printer.print_block(lv, block, to_console, to_log_file);
+}
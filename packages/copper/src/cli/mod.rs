@@ -34,6 +34,17 @@
 //! - `--verbose`/`-v` to increase verbose level.
 //! - `--quiet`/`-q` to decrease verbose level.
 //! - `--color` to set color mode
+//! - `--charset` to use ASCII instead of unicode glyphs for spinners and tree lines.
+//!   By default, ASCII is used when `TERM=dumb` or the locale is not UTF-8
+//! - `--log-format` to set the output format. `json` prints one JSON object per line
+//!   (with level, thread name, source, timestamp and message) instead of
+//!   human-readable text. Progress bar results and prompts are also printed as records,
+//!   and progress bars are not animated. `github` prints errors and warnings as
+//!   GitHub Actions workflow commands (i.e. annotations), and is the default
+//!   when running in GitHub Actions
+//! - `--log-file` to also write all messages to a file. The file
+//!   has no colors, has a timestamp on every line, and always logs at the
+//!   most verbose level (`RUST_LOG` still applies)
 //!
 //! The `prompt` feature enables these additional options:
 //! - `--yes`/`-y` to answer `y` to all yes/no prompts.
@@ -42,6 +53,16 @@
 //!     blocked
 //! - `--interactive`: This is the default, and cancels the effect of one `--non-interactive`
 //!
+//! There are also hidden options that print to stdout and exit without calling the main function:
+//! - `--generate-completion <SHELL>` to print the shell completion script. The supported
+//!   shells are `bash`, `elvish`, `fish`, `powershell` and `zsh`.
+//! - `--generate-man` to print the man page.
+//!
+//! With `#[cu::cli(env_prefix = "MYTOOL")]`, options not specified on the command line
+//! are read from environment variables like `MYTOOL_VERBOSE` and `MYTOOL_COLOR`,
+//! see [`Flags::apply_env`]. When the color mode is `auto`, the `NO_COLOR`,
+//! `CLICOLOR`, `CLICOLOR_FORCE` and `FORCE_COLOR` conventions are also respected.
+//!
 //! The [`cu::cli`](macro@crate::cli) macro generates a shim
 //! to parse the flags and pass it to your main function.
 //! It also handles the `Result` returned back. See the example
@@ -82,6 +103,10 @@
 //! }
 //! ```
 //!
+//! For programs with subcommands, the macro can annotate a `clap::Subcommand` enum
+//! to generate the dispatch to the handler of each subcommand.
+//! See the documentation for the macro and the `subcommand` example.
+//!
 //! # Printing and Logging
 //! In addition to the logging macros re-exported from the [`log`](https://docs.rs/log)
 //! crate, `cu` provides `print` and `hint` macros:
@@ -108,14 +133,39 @@
 //! When mixing `RUST_LOG` and verbosity flags, logging messages are filtered
 //! by `RUST_LOG`, and the verbosity would only apply to `print` and `hint`
 //!
+//! The number of warnings and errors printed is available from [`cu::cli::warning_count`]
+//! and [`cu::cli::error_count`], and is included in the `finished in ...` line
+//! printed at exit, for example `finished in 1.20s with 4 warnings, 1 error`.
+//! Call [`cu::lv::enable_recap`](crate::lv::enable_recap) to also print the warnings and errors
+//! again at exit, so they are not lost in a long output.
+//!
+//! # Data Output
+//! The result of the program (i.e. data) should be printed with [`cu::output!`](crate::output)
+//! instead, which always goes to stdout without any formatting. Messages and progress bars
+//! are printed to stderr when stdout is not terminal, or once `output!` is used,
+//! so piping the program to another one (for example, `mytool list | jq`)
+//! only pipes the data.
+//!
 //! # Other
 //! When setting up test, you can use [`cu::cli::level`] to quickly inititialize logging
-//! without dealing with the details.
+//! without dealing with the details. To assert on what is printed,
+//! use [`cu::cli::capture`] to record the messages, progress bars and prompts in memory.
 //!
 //! [`cu::cli::set_thread_name`] can be used to add a prefix to all messages printed
-//! by the current thread.
+//! by the current thread. To group messages from multi-stage tasks,
+//! see [Scopes](fn@crate::cli::scope).
 //!
 //! Messages that are too long and multi-line messages are automatically wrapped.
+//! Use [`cu::link`](fn@crate::link) to print a path as a clickable hyperlink
+//! when colors are enabled.
+//!
+//! The [`cu::cli`](macro@crate::cli) macro also installs a panic hook, which stops
+//! the progress bar animation before printing the panic as an error.
+//! The backtrace is printed with `-vv`. Panics on other threads could be caught,
+//! so they are printed as a warning, and the bars keep animating.
+//!
+//! The colors and prefix symbols can be customized with a [`Theme`].
+//! To highlight part of a message, see [Styled Text](mod@crate::style).
 //!
 //! # Manual Parsing CLI args
 //! [`cu::cli::try_parse`](crate::cli::try_parse)
@@ -133,25 +183,70 @@
 //! # Prompting
 //! See [Prompting](fn@crate::prompt)
 //!
+//! # Tables and Trees
+//! See [`Table`] and [`Tree`] for printing structured data
+//!
+//! # Pager
+//! See [`pager`](fn@crate::cli::pager) for sending long output to `$PAGER`.
+//! Requires the `process` feature
+//!
 #[cfg(feature = "cli")]
 mod flags;
 #[cfg(all(feature = "coroutine", feature = "cli"))]
 pub use flags::__co_run;
 #[cfg(feature = "cli")]
-pub use flags::{__run, Flags, print_help, try_parse};
+pub use flags::{__run, CompletionShell, Flags, print_help, try_parse};
+#[cfg(feature = "cli")]
+mod panic_hook;
 
 mod print_init;
 pub use print_init::{DefaultLogConfig, LogConfig, level};
 mod macros;
-pub use macros::__print_with_level;
+#[cfg(all(feature = "process", feature = "json"))]
+pub(crate) use macros::print_annotated;
+pub(crate) use macros::print_block;
+pub use macros::{__output, __print_with_level};
 
 mod thread_name;
 use thread_name::THREAD_NAME;
 pub use thread_name::{reset_thread_name, set_thread_name};
+mod scope;
+pub use scope::{Scope, scope};
+mod summary;
+pub use summary::{error_count, warning_count};
+mod log_file;
 mod printer;
+#[cfg(all(feature = "process", feature = "json"))]
+pub(crate) use printer::Annotation;
+pub(crate) use printer::Block;
+mod theme;
+pub(crate) use theme::ColorSupport;
+pub use theme::{LevelStyle, Theme, ThemeColor};
+
+mod table;
+pub use table::{Align, Table};
+mod tree;
+pub use tree::Tree;
+mod link;
+pub use link::{Link, link};
+
+#[cfg(feature = "process")]
+mod pager;
+#[cfg(feature = "process")]
+pub use pager::{Pager, pager};
+
+mod capture;
+#[cfg(feature = "prompt")]
+pub use capture::CapturedPrompt;
+pub use capture::{Capture, CapturedBar, capture};
 
 mod progress;
-pub use progress::{ProgressBar, ProgressBarBuilder, ProgressHandle, progress};
+#[cfg(feature = "coroutine")]
+pub use progress::ProgressStream;
+pub use progress::{
+    ProgressBar, ProgressBarBuilder, ProgressHandle, ProgressIter, ProgressIterExtension,
+    ProgressReader, ProgressTemplate, ProgressWriter, progress,
+};
 
 #[cfg(feature = "prompt")]
 mod prompt;
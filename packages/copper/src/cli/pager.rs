@@ -0,0 +1,246 @@
+use std::io::IsTerminal as _;
+use std::process::Stdio;
+
+use tokio::io::AsyncWriteExt as _;
+use tokio::process::{Child as TokioChild, ChildStdin, Command as TokioCommand};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::cli::printer::PRINTER;
+use crate::pio::{ChildInConfig, ChildInTask};
+use crate::str::PathExtension as _;
+use crate::{BoxedFuture, Context as _, Spawn as _};
+
+/// The pager command used if `PAGER` is not set
+const DEFAULT_PAGER: &str = "less -R";
+
+/// # Pager
+/// Send the output to a pager (`$PAGER`, or `less -R` by default)
+/// while the returned guard is alive. This is useful for commands
+/// that print a long report.
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// # fn main() -> cu::Result<()> {
+/// let pager = cu::cli::pager();
+/// for i in 0..500 {
+///     cu::print!("line {i}");
+/// }
+/// // blocks until the user quits the pager
+/// drop(pager);
+/// # Ok(()) }
+/// ```
+///
+/// The pager is only used if stdout is a terminal, and the output format is not `json`.
+/// Otherwise, the guard does nothing and messages are printed normally.
+///
+/// Messages, tables, and the result of progress bars are sent to the pager.
+/// The progress bars are not animated while paging, since the pager
+/// takes over the terminal. The color settings are kept, and `LESS=FRX` is set
+/// if `LESS` is not set, so `less` keeps the colors and exits immediately
+/// if the output fits on one screen.
+///
+/// When the guard is dropped, it waits for the user to quit the pager,
+/// then restores normal printing. In async code, use [`co_close`](Pager::co_close)
+/// instead, since blocking is not possible inside the runtime. If the guard
+/// is dropped inside the runtime, the pager is waited for in the background.
+///
+/// If the user quits the pager before the guard is dropped, messages
+/// printed afterwards are printed normally.
+pub fn pager() -> Pager {
+    match start_pager() {
+        Ok(child) => Pager { child },
+        Err(e) => {
+            crate::debug!("not using pager: {e:?}");
+            Pager { child: None }
+        }
+    }
+}
+
+/// Guard for paging the output. See [`pager`]
+#[must_use]
+pub struct Pager {
+    child: Option<crate::Child>,
+}
+
+impl Pager {
+    /// Check if the output is being sent to a pager
+    pub fn is_active(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Stop paging and wait for the user to quit the pager asynchronously
+    pub async fn co_close(mut self) {
+        let Some(child) = self.close() else {
+            return;
+        };
+        if let Err(e) = child.co_wait().await {
+            crate::debug!("failed to wait for pager: {e:?}");
+        }
+    }
+
+    /// Restore normal printing and take the pager process to wait for
+    fn close(&mut self) -> Option<crate::Child> {
+        let child = self.child.take()?;
+        if let Ok(mut printer) = PRINTER.lock()
+            && let Some(printer) = printer.as_mut()
+        {
+            // this closes the stdin of the pager
+            printer.stop_pager();
+        }
+        Some(child)
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let Some(child) = self.close() else {
+            return;
+        };
+        if tokio::runtime::Handle::try_current().is_ok() {
+            // blocking inside the runtime will panic
+            crate::debug!("pager is dropped in async context, use co_close() to wait for it");
+            let _ = crate::co::spawn(async move {
+                if let Err(e) = child.co_wait().await {
+                    crate::debug!("failed to wait for pager: {e:?}");
+                }
+            });
+            return;
+        }
+        if let Err(e) = child.wait() {
+            crate::debug!("failed to wait for pager: {e:?}");
+        }
+    }
+}
+
+fn start_pager() -> crate::Result<Option<crate::Child>> {
+    if !std::io::stdout().is_terminal() {
+        return Ok(None);
+    }
+    {
+        let printer = PRINTER.lock().ok();
+        let can_page = printer
+            .as_ref()
+            .and_then(|x| x.as_ref())
+            .is_some_and(|x| x.can_start_pager());
+        if !can_page {
+            return Ok(None);
+        }
+    }
+    let pager = crate::env_var("PAGER")
+        .ok()
+        .filter(|x| !x.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let mut parts = pager.split_whitespace();
+    let bin = crate::check!(parts.next(), "PAGER is empty")?;
+    let mut command = crate::which(bin)?.command().args(parts);
+    if std::env::var_os("LESS").is_none() {
+        command = command.env("LESS", "FRX");
+    }
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let child = command
+        .stdin(PagerInput(receiver))
+        .stdout_inherit()
+        .stderr_inherit()
+        .spawn()?;
+    let Ok(mut printer) = PRINTER.lock() else {
+        crate::bail!("failed to lock printer");
+    };
+    let Some(printer) = printer.as_mut() else {
+        crate::bail!("printer is not initialized");
+    };
+    printer.start_pager(sender);
+    Ok(Some(child))
+}
+
+/// Sender for the output to be written to the pager
+pub(crate) type PagerSender = UnboundedSender<String>;
+
+/// Stdin config of the pager, which writes the output received
+/// from the printer
+struct PagerInput(UnboundedReceiver<String>);
+impl ChildInConfig for PagerInput {
+    type Task = PagerInputTask;
+
+    fn configure_stdin(&mut self, command: &mut TokioCommand) -> crate::Result<()> {
+        command.stdin(Stdio::piped());
+        Ok(())
+    }
+
+    fn take(self, child: &mut TokioChild) -> crate::Result<Self::Task> {
+        let stdin = child
+            .stdin
+            .take()
+            .context("unexpected: pipe was already taken")?;
+        Ok(PagerInputTask {
+            receiver: self.0,
+            stdin,
+        })
+    }
+}
+struct PagerInputTask {
+    receiver: UnboundedReceiver<String>,
+    stdin: ChildStdin,
+}
+impl ChildInTask for PagerInputTask {
+    fn run(mut self) -> Option<BoxedFuture<crate::Result<()>>> {
+        Some(Box::pin(async move {
+            while let Some(text) = self.receiver.recv().await {
+                if self.stdin.write_all(text.as_bytes()).await.is_err() {
+                    // the user quit the pager. close the channel so the printer
+                    // goes back to stdout, and print what is already sent
+                    self.receiver.close();
+                    let mut stdout = std::io::stdout();
+                    let _ = std::io::Write::write_all(&mut stdout, text.as_bytes());
+                    while let Some(text) = self.receiver.recv().await {
+                        let _ = std::io::Write::write_all(&mut stdout, text.as_bytes());
+                    }
+                    let _ = std::io::Write::flush(&mut stdout);
+                    break;
+                }
+            }
+            Ok(())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_child() -> crate::Child {
+        crate::which("cargo")
+            .unwrap()
+            .command()
+            .arg("--version")
+            .stdin_null()
+            .stdout_null()
+            .stderr_null()
+            .spawn()
+            .unwrap()
+    }
+
+    /// Run the future like the async main function
+    fn block_on<F: Future>(future: F) -> F::Output {
+        #[cfg(not(feature = "coroutine-heavy"))]
+        let output = crate::co::block(future);
+        #[cfg(feature = "coroutine-heavy")]
+        let output = crate::co::run(future);
+        output
+    }
+
+    #[test]
+    fn test_drop_in_async_context() {
+        let pager = Pager {
+            child: Some(spawn_child()),
+        };
+        block_on(async move { drop(pager) });
+    }
+
+    #[test]
+    fn test_co_close() {
+        let pager = Pager {
+            child: Some(spawn_child()),
+        };
+        block_on(async move { pager.co_close().await });
+    }
+}
@@ -0,0 +1,76 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::sync::TryLockError;
+use std::time::{Duration, Instant};
+
+use crate::cli::printer::{PRINTER, Printer};
+
+/// Install the panic hook that prints the panic message with the printer.
+///
+/// The previous hook is used if the printer cannot be used, for example
+/// if the panic happened while printing
+pub(crate) fn install() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if !print_panic(info) {
+            previous_hook(info);
+        }
+    }));
+}
+
+fn print_panic(info: &PanicHookInfo<'_>) -> bool {
+    let thread = std::thread::current();
+    // only a panic on the main thread is known to end the process.
+    // panics on other threads could be caught (for example, when joining
+    // the thread or a task), so the bars are kept and it's reported as a warning
+    let is_fatal = thread.name() == Some("main");
+    if is_fatal {
+        let Some(handle) = with_printer(Printer::stop_for_panic) else {
+            return false;
+        };
+        if let Some(handle) = handle
+            && handle.thread().id() != thread.id()
+        {
+            let _: Result<_, _> = handle.join();
+        }
+    } else if with_printer(|_| ()).is_none() {
+        return false;
+    }
+    let message = crate::best_effort_panic_info_unboxed(info.payload());
+    let mut report = format!("thread '{}' panicked", thread.name().unwrap_or("<unnamed>"));
+    if let Some(location) = info.location() {
+        let _ = write!(report, " at {location}");
+    }
+    let _ = write!(report, ":\n{message}");
+    // -vv sets RUST_BACKTRACE
+    let backtrace = Backtrace::capture();
+    if backtrace.status() == BacktraceStatus::Captured {
+        let _ = write!(report, "\n\nbacktrace:\n{backtrace}");
+    }
+    if is_fatal {
+        crate::error!("{report}");
+    } else {
+        crate::warn!("{report}");
+    }
+    true
+}
+
+/// Run `f` with the printer. Returns `None` if the printer is not available
+fn with_printer<T>(f: impl FnOnce(&mut Printer) -> T) -> Option<T> {
+    // the panicking thread could be the one holding the lock,
+    // so we only wait for a short time
+    let start = Instant::now();
+    loop {
+        match PRINTER.try_lock() {
+            Ok(mut printer) => return printer.as_mut().map(f),
+            Err(TryLockError::Poisoned(_)) => return None,
+            Err(TryLockError::WouldBlock) => {
+                if start.elapsed() > Duration::from_millis(100) {
+                    return None;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+}
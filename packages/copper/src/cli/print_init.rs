@@ -1,9 +1,13 @@
+use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, OnceLock};
 
-use cu::cli::printer::{PRINTER, Printer};
+use cu::cli::log_file::{self, LogFile};
+use cu::cli::printer::{PRINTER, Printer, Source};
 #[cfg(feature = "prompt")]
 use cu::cli::prompt::PROMPT_LEVEL;
+use cu::cli::summary;
+use cu::cli::theme::Theme;
 use cu::lv;
 use env_filter::{Builder as LogEnvBuilder, Filter as LogEnvFilter};
 
@@ -22,24 +26,54 @@ pub fn level(lv: &str) {
         "vv" => lv::Print::VerboseVerbose,
         _ => lv::Print::Normal,
     };
-    init_options(
-        lv::Color::Auto,
+    init_options(PrintOptions {
         level,
-        Some(lv::Prompt::Block),
-        Arc::new(DefaultLogConfig),
-    );
+        format: Some(lv::Format::Text),
+        prompt: Some(lv::Prompt::Block),
+        ..Default::default()
+    });
+}
+
+/// Global print options, see [`init_options`]
+pub(crate) struct PrintOptions<'a> {
+    pub color: lv::Color,
+    pub level: lv::Print,
+    /// If `None`, it will be `Github` if env var `CI` and `GITHUB_ACTIONS`
+    /// are `true` or `1`, otherwise `Text`
+    pub format: Option<lv::Format>,
+    /// If `None`, it will be `Interactive` unless env var `CI` is `true` or `1`,
+    /// in which case it becomes `Block`. Ignored unless `prompt` feature is enabled
+    pub prompt: Option<lv::Prompt>,
+    pub log_config: Arc<dyn LogConfig + Send + Sync>,
+    /// If set, all messages are also written to the file,
+    /// regardless of the print level
+    pub log_file: Option<&'a Path>,
+}
+impl Default for PrintOptions<'_> {
+    fn default() -> Self {
+        Self {
+            color: lv::Color::Auto,
+            level: lv::Print::Normal,
+            format: None,
+            prompt: None,
+            log_config: Arc::new(DefaultLogConfig),
+            log_file: None,
+        }
+    }
 }
 
 /// Set global print options. This is usually called from clap args
-///
-/// If prompt option is `None`, it will be `Interactive` unless env var `CI` is `true` or `1`, in which case it becomes `No`.
-/// Prompt option is ignored unless `prompt` feature is enabled
-pub fn init_options(
-    color: lv::Color,
-    level: lv::Print,
-    prompt: Option<lv::Prompt>,
-    log_config: Arc<dyn LogConfig + Send + Sync>,
-) {
+pub(crate) fn init_options(options: PrintOptions<'_>) {
+    let PrintOptions {
+        color,
+        level,
+        format,
+        prompt,
+        log_config,
+        log_file,
+    } = options;
+    let log_file = log_file.map(|path| (path, LogFile::open(path)));
+    let has_log_file = matches!(log_file, Some((_, Ok(_))));
     // not using cu::env_var, since we are before log initialization
     let env_rust_log = std::env::var("RUST_LOG");
     let (log_level_filter, log_filter) = match env_rust_log {
@@ -48,13 +82,38 @@ pub fn init_options(
             let log_level_filter = filter.filter();
             (log_level_filter.max(level.into()), Some(filter))
         }
+        // the log file always logs everything
+        _ if has_log_file => (log::LevelFilter::Trace, None),
         _ => (level.into(), None),
     };
     log::set_max_level(log_level_filter);
 
-    let use_color = color.is_colored_for_stdout();
+    let format = match format {
+        Some(x) => x,
+        None => {
+            if is_env_true("CI") && is_env_true("GITHUB_ACTIONS") {
+                lv::Format::Github
+            } else {
+                lv::Format::Text
+            }
+        }
+    };
+    // records are never colored
+    let use_color = format != lv::Format::Json && color.is_colored_for_stdout();
     lv::USE_COLOR.store(use_color, Ordering::Release);
-    let printer = Printer::new(use_color);
+    if lv::is_taskbar_progress_supported() {
+        lv::enable_taskbar_progress();
+    }
+    let mut printer = Printer::new(use_color, format, &log_config.theme());
+    let log_file_error = match log_file {
+        Some((_, Ok(file))) => {
+            printer.set_log_file(Some(file));
+            None
+        }
+        Some((path, Err(e))) => Some((path, e)),
+        None => None,
+    };
+    log_file::set_enabled(has_log_file);
     if let Ok(mut g_printer) = PRINTER.lock() {
         *g_printer = Some(printer);
     }
@@ -63,13 +122,7 @@ pub fn init_options(
         let prompt = match prompt {
             Some(x) => x,
             None => {
-                let is_ci = std::env::var("CI")
-                    .map(|mut x| {
-                        x.make_ascii_lowercase();
-                        matches!(x.trim(), "true" | "1")
-                    })
-                    .unwrap_or_default();
-                if is_ci {
+                if is_env_true("CI") {
                     lv::Prompt::Block
                 } else {
                     lv::Prompt::Interactive
@@ -89,8 +142,27 @@ pub fn init_options(
         filter: log_filter,
         config: log_config,
     });
-    log::set_logger(LOGGER.get().unwrap()).unwrap();
+    // setting the logger again is a no-op, so this can be called multiple times
+    let _ = log::set_logger(LOGGER.get().unwrap());
+
+    if let Some((path, e)) = log_file_error {
+        crate::warn!("failed to open log file '{}': {e}", path.display());
+    }
 }
+/// Check if logging has been initialized
+pub(crate) fn is_initialized() -> bool {
+    LOGGER.get().is_some()
+}
+fn is_env_true(name: &str) -> bool {
+    // not using cu::env_var, since we are before log initialization
+    std::env::var(name)
+        .map(|mut x| {
+            x.make_ascii_lowercase();
+            matches!(x.trim(), "true" | "1")
+        })
+        .unwrap_or_default()
+}
+
 struct LogImpl {
     filter: Option<LogEnvFilter>,
     config: Arc<dyn LogConfig + Send + Sync>,
@@ -105,43 +177,68 @@ impl log::Log for LogImpl {
 
     fn log(&self, record: &log::Record) {
         let (level, show_module) = self.config.process(record);
-        if level != record.level().into() {
+        let (to_console, to_log_file) = if level != record.level().into() {
             let metadata = log::Metadata::builder()
                 .level(level.into())
                 .target(record.metadata().target())
                 .build();
-            if !self.enabled(&metadata) {
-                return;
-            }
+            (self.enabled(&metadata), self.log_file_enabled(&metadata))
         } else {
-            if !self.enabled(record.metadata()) {
-                return;
-            }
+            (
+                self.enabled(record.metadata()),
+                self.log_file_enabled(record.metadata()),
+            )
+        };
+        if !to_console && !to_log_file {
+            return;
         }
-        let message = if show_module {
+        let mut message = String::new();
+        if show_module {
             // enable source location logging in trace messages
-            let mut message = String::new();
             format_module_prefix(
                 &mut message,
                 record.module_path(),
                 record.file(),
                 record.line(),
             );
-            use std::fmt::Write;
-            let _: Result<_, _> = write!(&mut message, "{}", record.args());
-            message
-        } else {
-            record.args().to_string()
+        }
+        let source = Source {
+            module: record.module_path(),
+            file: record.file(),
+            line: record.line(),
+            prefix_len: message.len(),
         };
+        use std::fmt::Write;
+        let _: Result<_, _> = write!(&mut message, "{}", record.args());
+        if to_console {
+            summary::record(level, &message[source.prefix_len..]);
+        }
         if let Ok(mut printer) = PRINTER.lock() {
             if let Some(printer) = printer.as_mut() {
-                printer.print_message(level, &message);
+                if to_log_file {
+                    printer.print_message_to_log_file(level, &message);
+                }
+                if to_console {
+                    printer.print_message(level, &message, &source);
+                }
             }
         }
     }
 
     fn flush(&self) {}
 }
+impl LogImpl {
+    /// The log file ignores the print level, but still respects `RUST_LOG`
+    fn log_file_enabled(&self, metadata: &log::Metadata) -> bool {
+        if !log_file::is_enabled() {
+            return false;
+        }
+        match &self.filter {
+            Some(filter) => filter.enabled(metadata),
+            None => true,
+        }
+    }
+}
 
 fn format_module_prefix(
     message: &mut String,
@@ -181,6 +278,11 @@ pub trait LogConfig {
     /// Process a log record, return the level to log and if
     /// the module path should be shown
     fn process(&self, record: &lv::LogRecord) -> (lv::Lv, bool);
+
+    /// Get the colors and symbols to use when printing. See [`Theme`](crate::cli::Theme)
+    fn theme(&self) -> Theme {
+        Theme::default()
+    }
 }
 /// The default [`LogConfig`]
 pub struct DefaultLogConfig;
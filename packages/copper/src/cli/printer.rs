@@ -4,17 +4,23 @@ use std::io::{self, IsTerminal as _};
 use std::ops::ControlFlow;
 use std::sync::{Arc, Mutex, Weak};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "prompt")]
 use oneshot::{Receiver as OnceRecv, Sender as OnceSend};
 
+use crate::cli::capture::{CapturedBar, Recording};
 #[cfg(feature = "prompt")]
 use crate::cli::ctrlc;
-use crate::cli::fmt::{self, FormatBuffer, ansi};
+use crate::cli::fmt::{self, FormatBuffer, JsonRecord, ansi};
+use crate::cli::log_file::LogFile;
+#[cfg(feature = "process")]
+use crate::cli::pager::PagerSender;
 use crate::cli::progress::{BarFormatter, BarResult, ProgressBar};
 #[cfg(feature = "prompt")]
 use crate::cli::prompter;
-use crate::cli::{THREAD_NAME, TICK_INTERVAL, Tick};
+use crate::cli::theme::{ColorSupport, Theme};
+use crate::cli::{THREAD_NAME, TICK_INTERVAL, Tick, scope};
 use crate::lv;
 
 /// Global printer state
@@ -28,12 +34,19 @@ pub(crate) struct Printer {
     stderr: io::Stderr,
     /// Color codes
     colors: ansi::Colors,
+    /// Output format of the messages
+    format: lv::Format,
+    /// Target for printing the messages. This is stdout,
+    /// unless stdout is not terminal or data output is used
+    message_target: Target,
 
     print_task: PrintingThreadHandle,
     /// Target for showing animation (bars and prompts)
     /// If None, bar animation will not be printed, and prompts
     /// will still be printed to stderr
     anime_target: Option<Target>,
+    /// Animated lines currently displayed on the animation target
+    anime_lines: AnimeLines,
     bars: Vec<Weak<ProgressBar>>,
     #[cfg(feature = "prompt")]
     pending_prompts: VecDeque<PromptTask>,
@@ -42,14 +55,31 @@ pub(crate) struct Printer {
     format_buffer: FormatBuffer,
     /// Place to buffer prints while printing is blocked
     buffered: String,
+    /// File to tee all messages to
+    log_file: Option<LogFile>,
+    /// If set, messages are recorded here instead of printed
+    capture: Option<Arc<Mutex<Recording>>>,
+    /// If set, output is sent to the pager instead of stdout,
+    /// along with the animation target to restore after paging
+    #[cfg(feature = "process")]
+    pager: Option<(PagerSender, Option<Target>)>,
 }
 impl Printer {
-    pub fn new(use_color: bool) -> Self {
-        let colors = ansi::colors(use_color);
+    pub fn new(use_color: bool, format: lv::Format, theme: &Theme) -> Self {
+        let color_support = use_color.then(ColorSupport::current);
+        let colors = ansi::Colors::new(theme, color_support);
         let stdout = io::stdout();
         let stderr = io::stderr();
         let is_stdin_terminal = io::stdin().is_terminal();
-        let anime_target = if cfg!(feature = "__test") {
+        let message_target = if cfg!(feature = "__test") || stdout.is_terminal() {
+            Target::Stdout
+        } else {
+            Target::Stderr
+        };
+        let anime_target = if format == lv::Format::Json {
+            // animations will mess up the records
+            None
+        } else if cfg!(feature = "__test") {
             Some(Target::Stdout)
         } else {
             if stdout.is_terminal() {
@@ -66,29 +96,160 @@ impl Printer {
             stdout,
             stderr,
             colors,
+            format,
+            message_target,
 
             print_task: Default::default(),
             anime_target,
+            anime_lines: AnimeLines {
+                width: fmt::term_width_or_max(),
+                ..Default::default()
+            },
             bars: Default::default(),
             #[cfg(feature = "prompt")]
             pending_prompts: Default::default(),
 
             format_buffer: FormatBuffer::new(),
             buffered: String::new(),
+            log_file: None,
+            capture: None,
+            #[cfg(feature = "process")]
+            pager: None,
+        }
+    }
+    /// Create a printer that records everything instead of printing
+    pub(crate) fn new_capture(recording: Arc<Mutex<Recording>>) -> Self {
+        let mut printer = Self::new(false, lv::Format::Text, &Theme::default());
+        printer.anime_target = None;
+        printer.capture = Some(recording);
+        printer
+    }
+    /// Check if the output can be sent to a pager
+    #[cfg(feature = "process")]
+    pub(crate) fn can_start_pager(&self) -> bool {
+        self.pager.is_none() && self.capture.is_none() && self.format != lv::Format::Json
+    }
+    /// Send the output to the pager instead of stdout. Animations are
+    /// disabled while paging, since the pager takes over the terminal
+    #[cfg(feature = "process")]
+    pub(crate) fn start_pager(&mut self, sender: PagerSender) {
+        if !self.buffered.is_empty() {
+            self.flush_buffered_messages();
+        }
+        let anime_target = self.anime_target.take();
+        self.pager = Some((sender, anime_target));
+    }
+    /// Restore printing to stdout. Dropping the sender closes the pager's stdin
+    #[cfg(feature = "process")]
+    pub(crate) fn stop_pager(&mut self) {
+        if !self.buffered.is_empty() {
+            self.flush_buffered_messages();
+        }
+        if let Some((_, anime_target)) = self.pager.take() {
+            self.anime_target = anime_target;
         }
     }
+    /// Send the text to the pager. If the user already quit the pager,
+    /// normal printing is restored and the text is returned back
+    #[cfg(feature = "process")]
+    fn send_to_pager(&mut self, text: String) -> Result<(), String> {
+        let Some((pager, _)) = &self.pager else {
+            return Err(text);
+        };
+        let Err(e) = pager.send(text) else {
+            return Ok(());
+        };
+        if let Some((_, anime_target)) = self.pager.take() {
+            self.anime_target = anime_target;
+        }
+        Err(e.0)
+    }
+    /// Print data to stdout, see [`cu::output!`](crate::output)
+    ///
+    /// Messages and animations are moved away from stdout, so they
+    /// don't mix with the data
+    pub(crate) fn print_output(&mut self, text: &str) {
+        if let Some(capture) = &self.capture {
+            let mut recording = capture.lock().unwrap_or_else(|e| e.into_inner());
+            recording.output.push_str(text);
+            return;
+        }
+        #[cfg(feature = "process")]
+        if self.pager.is_some() && self.send_to_pager(text.to_string()).is_ok() {
+            return;
+        }
+        self.message_target = Target::Stderr;
+        if self.anime_target == Some(Target::Stdout) {
+            if self.anime_lines.lines > 0 {
+                // erase the bars from stdout before moving them,
+                // otherwise they are left above the output
+                let mut clear_codes = String::new();
+                self.anime_lines.format_clear_codes(&mut clear_codes);
+                Target::Stdout.write(&mut self.stdout, &mut self.stderr, &clear_codes);
+            }
+            self.anime_target = if cfg!(feature = "__test") || self.stderr.is_terminal() {
+                Some(Target::Stderr)
+            } else {
+                None
+            };
+        }
+        use std::io::Write as _;
+        let _ = write!(self.stdout, "{text}");
+        let _ = self.stdout.flush();
+    }
+    /// Set the file to tee all messages to
+    pub(crate) fn set_log_file(&mut self, log_file: Option<LogFile>) {
+        self.log_file = log_file;
+    }
     #[cfg(feature = "prompt")]
     pub(crate) fn show_prompt(
         &mut self,
         prompt: &str,
         is_password: bool,
     ) -> OnceRecv<cu::Result<Option<cu::ZString>>> {
+        let (send, recv) = oneshot::channel();
+        if let Some(capture) = &self.capture {
+            let mut recording = capture.lock().unwrap_or_else(|e| e.into_inner());
+            let answer = recording.answers.pop_front();
+            recording.prompts.push(crate::cli::CapturedPrompt {
+                prompt: prompt.to_string(),
+                is_password,
+                answer: answer.clone(),
+            });
+            let result = match answer {
+                Some(answer) => Ok(Some(answer.into())),
+                None => Err(crate::fmterr!("no answer queued for captured prompt")),
+            };
+            let _ = send.send(result);
+            return recv;
+        }
+        if self.format == lv::Format::Json {
+            self.format_buffer.reset("", "");
+            let mut record = String::new();
+            JsonRecord::new(&mut record, "prompt")
+                .str("message", prompt)
+                .str("kind", if is_password { "password" } else { "text" })
+                .finish();
+            self.format_buffer.push_control(&record);
+            self.print_format_buffer();
+            // the record replaces the human-readable prompt,
+            // the answer is still read from stdin
+            self.pending_prompts.push_back(PromptTask {
+                send,
+                prompt: String::new(),
+                is_password,
+            });
+            self.start_print_task_if_needed();
+            return recv;
+        }
         // format the prompt
         let mut lines = prompt.lines();
-        self.format_buffer.reset(self.colors.gray, self.colors.cyan);
-        self.format_buffer.push_control(self.colors.cyan);
-        self.format_buffer.push('!', 1);
-        self.format_buffer.push(']', 1);
+        let style = self.colors.prompt;
+        self.format_buffer.reset(self.colors.decoration, style.text);
+        self.format_buffer.push_prefix(&style);
+        if style.text != style.separator_color {
+            self.format_buffer.push_control(style.text);
+        }
         if let Some(line) = lines.next() {
             self.format_buffer.push(' ', 1);
             self.format_buffer.push_str(line);
@@ -105,7 +266,6 @@ impl Printer {
             self.format_buffer.push_control("-: ");
         }
 
-        let (send, recv) = oneshot::channel();
         self.pending_prompts.push_back(PromptTask {
             send,
             prompt: self.format_buffer.take(),
@@ -120,7 +280,9 @@ impl Printer {
         if lv::PRINT_LEVEL.get() < lv::Print::Quiet {
             return;
         }
-        if self.anime_target.is_none() {
+        if self.anime_target.is_none()
+            && (!self.should_report_progress() || lv::progress_interval().is_zero())
+        {
             return;
         }
         // start the bar
@@ -134,31 +296,117 @@ impl Printer {
             self.print_task.assign(PrintingThread::spawn());
         }
     }
-    /// Print a progress bar done message
-    pub(crate) fn print_bar_done(&mut self, result: &BarResult, is_root: bool) {
+    /// If the status of the bars should be printed periodically,
+    /// because they are not animated
+    fn should_report_progress(&self) -> bool {
+        #[cfg(feature = "process")]
+        if self.pager.is_some() {
+            return false;
+        }
+        self.anime_target.is_none() && self.capture.is_none()
+    }
+    /// Print the status of each bar in one line
+    fn print_progress_report(&mut self, tick: Tick) {
         if lv::PRINT_LEVEL.get() < lv::Print::Normal {
             return;
         }
+        let mut now = None;
+        let mut status = String::new();
+        let mut temp = String::new();
+        for bar in &self.bars {
+            let Some(bar) = bar.upgrade() else {
+                continue;
+            };
+            status.clear();
+            let mut formatter = BarFormatter {
+                colors: self.colors,
+                bar_color: self.colors.progress,
+                // the status lines are not truncated, and the flexible
+                // bar in templates is capped
+                width: usize::MAX / 2,
+                tick,
+                now: &mut now,
+                out: &mut status,
+                temp: &mut temp,
+            };
+            bar.format_status(&mut formatter);
+            if self.format == lv::Format::Json {
+                self.format_buffer.reset("", "");
+                let mut record = String::new();
+                JsonRecord::new(&mut record, "progress")
+                    .str("state", "running")
+                    .str("message", status.trim())
+                    .finish();
+                self.format_buffer.push_control(&record);
+            } else {
+                self.format_buffer
+                    .reset(self.colors.decoration, self.colors.progress);
+                self.format_buffer.push_control(self.colors.progress);
+                self.format_buffer.push_control(fmt::glyphs().progress);
+                self.format_buffer.push_control("]");
+                // the status starts with either "[" or " "
+                self.format_buffer.push_str(&status);
+                self.format_buffer.push_lf();
+            }
+            self.buffered.push_str(self.format_buffer.as_str());
+        }
+    }
+    /// Print a progress bar done message
+    pub(crate) fn print_bar_done(&mut self, result: &BarResult, is_root: bool) {
+        if let Some(log_file) = &mut self.log_file {
+            log_file.write_bar_done(result);
+        }
         if !is_root && self.anime_target.is_some() {
             // if bar is animated, don't print child's done messages
             return;
         }
+        if lv::PRINT_LEVEL.get() < lv::Print::Normal {
+            return;
+        }
+        if let Some(capture) = &self.capture {
+            let bar = match result {
+                BarResult::DontKeep => CapturedBar::Done(None),
+                BarResult::Done(message) => CapturedBar::Done(Some(message.clone())),
+                BarResult::Interrupted(message) => CapturedBar::Interrupted(message.clone()),
+            };
+            let mut recording = capture.lock().unwrap_or_else(|e| e.into_inner());
+            recording.bars.push(bar);
+            return;
+        }
+        if self.format == lv::Format::Json {
+            let (state, message) = match result {
+                BarResult::DontKeep => return,
+                BarResult::Done(message) => ("done", message),
+                BarResult::Interrupted(message) => ("interrupted", message),
+            };
+            self.format_buffer.reset("", "");
+            let mut record = String::new();
+            JsonRecord::new(&mut record, "progress")
+                .str("state", state)
+                .str("message", message)
+                .finish();
+            self.format_buffer.push_control(&record);
+            self.print_format_buffer();
+            return;
+        }
         let message = match result {
             BarResult::DontKeep => return,
             BarResult::Done(message) => {
                 self.format_buffer
-                    .reset(self.colors.gray, self.colors.green);
-                self.format_buffer.push_control(self.colors.green);
+                    .reset(self.colors.decoration, self.colors.progress_done);
+                self.format_buffer.push_control(self.colors.progress_done);
                 message
             }
             BarResult::Interrupted(message) => {
                 self.format_buffer
-                    .reset(self.colors.gray, self.colors.yellow);
-                self.format_buffer.push_control(self.colors.yellow);
+                    .reset(self.colors.decoration, self.colors.progress_interrupted);
+                self.format_buffer
+                    .push_control(self.colors.progress_interrupted);
                 message
             }
         };
-        self.format_buffer.push_control("\u{283f}]");
+        self.format_buffer.push_control(fmt::glyphs().progress);
+        self.format_buffer.push_control("]");
         if !message.starts_with('[') {
             self.format_buffer.push_control(" ");
         }
@@ -167,63 +415,55 @@ impl Printer {
         self.print_format_buffer();
     }
 
-    /// Format and print the message
-    pub(crate) fn print_message(&mut self, lv: lv::Lv, message: &str) {
-        let mut lines = message.lines();
-        let text_color = match lv {
-            lv::Lv::Off => return,
-            lv::Lv::Error => self.colors.red,
-            lv::Lv::Hint => self.colors.yellow,
-            lv::Lv::Print => self.colors.reset,
-            lv::Lv::Warn => self.colors.yellow,
-            lv::Lv::Info => self.colors.reset,
-            lv::Lv::Debug => self.colors.cyan,
-            lv::Lv::Trace => self.colors.magenta,
-        };
-        self.format_buffer.reset(self.colors.gray, text_color);
-        match lv {
-            lv::Lv::Off => unreachable!(),
-            lv::Lv::Error => {
-                self.format_buffer.push_control(self.colors.red);
-                self.format_buffer.push('E', 1);
-                self.format_buffer.push(']', 1);
-            }
-            lv::Lv::Hint => {
-                self.format_buffer.push_control(self.colors.cyan);
-                self.format_buffer.push('H', 1);
-                self.format_buffer.push_control(self.colors.gray);
-                self.format_buffer.push(']', 1);
-            }
-            lv::Lv::Print => {
-                self.format_buffer.push_control(self.colors.gray);
-                self.format_buffer.push(':', 1);
-                self.format_buffer.push(':', 1);
-            }
-            lv::Lv::Warn => {
-                self.format_buffer.push_control(self.colors.yellow);
-                self.format_buffer.push('W', 1);
-                self.format_buffer.push(']', 1);
-            }
-            lv::Lv::Info => {
-                self.format_buffer.push_control(self.colors.green);
-                self.format_buffer.push('I', 1);
-                self.format_buffer.push_control(self.colors.gray);
-                self.format_buffer.push(']', 1);
-            }
-            lv::Lv::Debug => {
-                self.format_buffer.push_control(self.colors.gray);
-                self.format_buffer.push('D', 1);
-                self.format_buffer.push(']', 1);
+    /// Print the recorded warnings and errors again after the header.
+    ///
+    /// In the Github format, the messages are printed as plain text,
+    /// since the annotations are already created when they are first printed
+    #[cfg(feature = "cli")]
+    pub(crate) fn print_recap(&mut self, header: &str, recap: &[(lv::Lv, String)]) {
+        let format = self.format;
+        if format == lv::Format::Github {
+            self.format = lv::Format::Text;
+        }
+        let source = Source::default();
+        self.print_message(lv::Lv::Print, header, &source);
+        for (lv, message) in recap {
+            self.print_message(*lv, message, &source);
+        }
+        self.format = format;
+    }
+
+    /// Format and print the message.
+    ///
+    /// `source` is only used for formats that display the source separately
+    pub(crate) fn print_message(&mut self, lv: lv::Lv, message: &str, source: &Source) {
+        if let Some(capture) = &self.capture {
+            let mut recording = capture.lock().unwrap_or_else(|e| e.into_inner());
+            let message = &message[source.prefix_len..];
+            recording.messages.push((lv, message.to_string()));
+            return;
+        }
+        match self.format {
+            lv::Format::Json => {
+                self.print_message_json(lv, message, source);
+                return;
             }
-            lv::Lv::Trace => {
-                self.format_buffer.push_control(self.colors.magenta);
-                self.format_buffer.push('*', 1);
-                self.format_buffer.push(']', 1);
+            lv::Format::Github if matches!(lv, lv::Lv::Error | lv::Lv::Warn) => {
+                self.print_workflow_command(lv, message, &Annotation::default());
+                return;
             }
+            _ => {}
         }
+        let mut lines = message.lines();
+        let Some(style) = self.colors.level(lv) else {
+            return;
+        };
+        let text_color = style.text;
+        self.format_buffer.reset(self.colors.decoration, text_color);
+        self.format_buffer.push_prefix(style);
         THREAD_NAME.with_borrow(|x| {
             if let Some(x) = x {
-                self.format_buffer.push_control(self.colors.magenta);
+                self.format_buffer.push_control(self.colors.thread_name);
                 self.format_buffer.push('[', 1);
                 self.format_buffer.push_str(x);
                 self.format_buffer.push(']', 1);
@@ -232,6 +472,7 @@ impl Printer {
         self.format_buffer.push_control(text_color);
         if let Some(line) = lines.next() {
             self.format_buffer.push(' ', 1);
+            self.format_buffer.push_indent(scope::depth());
             self.format_buffer.push_str(line);
         }
         for line in lines {
@@ -241,22 +482,172 @@ impl Printer {
         self.format_buffer.push_lf();
         self.print_format_buffer();
     }
+    fn print_message_json(&mut self, lv: lv::Lv, message: &str, source: &Source) {
+        if lv == lv::Lv::Off {
+            return;
+        }
+        self.format_buffer.reset("", "");
+        let mut record = String::new();
+        let record_builder = JsonRecord::new(&mut record, "message").str("level", &lv.to_string());
+        let record_builder =
+            THREAD_NAME.with_borrow(|x| record_builder.opt_str("thread", x.as_deref()));
+        let record_builder = scope::SCOPES.with_borrow(|x| {
+            let scope = x.join(" > ");
+            record_builder.opt_str("scope", Some(scope.as_str()).filter(|x| !x.is_empty()))
+        });
+        record_builder
+            .opt_str("module", source.module)
+            .opt_str("file", source.file)
+            .opt_u32("line", source.line)
+            .str("message", &message[source.prefix_len..])
+            .finish();
+        self.format_buffer.push_control(&record);
+        self.print_format_buffer();
+    }
+    /// Print a message that refers to a location in a file, as a GitHub Actions annotation
+    /// if enabled, otherwise same as [`print_message`](Self::print_message)
+    #[cfg(all(feature = "process", feature = "json"))]
+    pub(crate) fn print_annotated(&mut self, lv: lv::Lv, message: &str, annotation: &Annotation) {
+        if self.format == lv::Format::Github && matches!(lv, lv::Lv::Error | lv::Lv::Warn) {
+            self.print_workflow_command(lv, message, annotation);
+        } else {
+            self.print_message(lv, message, &Source::default());
+        }
+    }
+    fn print_workflow_command(&mut self, lv: lv::Lv, message: &str, annotation: &Annotation) {
+        self.format_buffer.reset("", "");
+        let mut command = String::from(if lv == lv::Lv::Error {
+            "::error"
+        } else {
+            "::warning"
+        });
+        let mut separator = ' ';
+        let mut push_property = |key: &str, value: &str| {
+            command.push(separator);
+            separator = ',';
+            command.push_str(key);
+            command.push('=');
+            fmt::push_workflow_property(&mut command, value);
+        };
+        if let Some(file) = annotation.file {
+            push_property("file", file);
+        }
+        for (key, value) in [
+            ("line", annotation.line),
+            ("endLine", annotation.end_line),
+            ("col", annotation.column),
+            ("endColumn", annotation.end_column),
+        ] {
+            if let Some(value) = value {
+                push_property(key, &value.to_string());
+            }
+        }
+        if let Some(title) = annotation.title {
+            push_property("title", title);
+        }
+        command.push_str("::");
+        THREAD_NAME.with_borrow(|x| {
+            if let Some(x) = x {
+                command.push('[');
+                fmt::push_workflow_data(&mut command, x);
+                command.push_str("] ");
+            }
+        });
+        fmt::push_workflow_data(&mut command, message.trim_end());
+        command.push('\n');
+        self.format_buffer.push_control(&command);
+        self.print_format_buffer();
+    }
+    /// Print a block of preformatted text, such as a table
+    pub(crate) fn print_block(
+        &mut self,
+        lv: lv::Lv,
+        block: &dyn Block,
+        to_console: bool,
+        to_log_file: bool,
+    ) {
+        if to_log_file && self.log_file.is_some() {
+            let mut text = String::new();
+            block.render(&mut text, None, &ansi::Colors::new(&Theme::default(), None));
+            self.print_message_to_log_file(lv, text.trim_end());
+        }
+        if !to_console {
+            return;
+        }
+        if let Some(capture) = &self.capture {
+            let mut text = String::new();
+            block.render(&mut text, None, &self.colors);
+            let mut recording = capture.lock().unwrap_or_else(|e| e.into_inner());
+            recording.messages.push((lv, text.trim_end().to_string()));
+            return;
+        }
+        self.format_buffer.reset("", "");
+        let mut text = String::new();
+        if self.format == lv::Format::Json {
+            block.json_record(&mut text);
+        } else {
+            block.render(&mut text, Some(fmt::term_width_or_max()), &self.colors);
+        }
+        self.format_buffer.push_control(&text);
+        self.print_format_buffer();
+    }
+    /// Write the message to the log file, if there is one
+    pub(crate) fn print_message_to_log_file(&mut self, lv: lv::Lv, message: &str) {
+        if let Some(log_file) = &mut self.log_file {
+            log_file.write_message(lv, message);
+        }
+    }
     fn print_format_buffer(&mut self) {
         if !self.print_task.active() {
-            use std::io::Write;
-            let _ = write!(self.stdout, "{}", self.format_buffer.as_str());
-            let _ = self.stdout.flush();
+            #[cfg(feature = "process")]
+            if self.pager.is_some()
+                && self
+                    .send_to_pager(self.format_buffer.as_str().to_string())
+                    .is_ok()
+            {
+                return;
+            }
+            self.message_target.write(
+                &mut self.stdout,
+                &mut self.stderr,
+                self.format_buffer.as_str(),
+            );
         } else {
             self.buffered.push_str(self.format_buffer.as_str());
         }
     }
-    fn flush_buffered_to_stdout(&mut self) {
-        use std::io::Write as _;
-        let _ = write!(self.stdout, "{}", self.buffered);
-        let _ = self.stdout.flush();
+    fn flush_buffered_messages(&mut self) {
+        #[cfg(feature = "process")]
+        if self.pager.is_some() {
+            let buffered = std::mem::take(&mut self.buffered);
+            match self.send_to_pager(buffered) {
+                Ok(()) => return,
+                Err(buffered) => self.buffered = buffered,
+            }
+        }
+        self.message_target
+            .write(&mut self.stdout, &mut self.stderr, &self.buffered);
         self.buffered.clear();
     }
 
+    /// Stop animating the progress bars, so the panic message does not
+    /// mix with the animation.
+    ///
+    /// Returns the print task to join after releasing the lock. The task
+    /// will erase the bars and flush the buffered messages before ending
+    #[cfg(feature = "cli")]
+    pub(crate) fn stop_for_panic(&mut self) -> Option<JoinHandle<()>> {
+        self.bars.clear();
+        // the print task could be blocked by a prompt
+        #[cfg(feature = "prompt")]
+        if !self.pending_prompts.is_empty() {
+            self.anime_target = None;
+            self.flush_buffered_messages();
+            return None;
+        }
+        self.print_task.take()
+    }
+
     pub(crate) fn take_print_task_if_should_join(&mut self) -> Option<JoinHandle<()>> {
         if self.print_task.needs_join {
             return self.print_task.take();
@@ -277,6 +668,38 @@ impl Printer {
     }
 }
 
+/// Preformatted content that is printed as a whole, such as a table
+pub(crate) trait Block {
+    /// Render the content as text lines, each ending with a new line.
+    /// The lines should fit in `max_width` if it's set
+    fn render(&self, out: &mut String, max_width: Option<usize>, colors: &ansi::Colors);
+    /// Format the content as a JSON record
+    fn json_record(&self, out: &mut String);
+}
+
+/// Where a message comes from
+#[derive(Default)]
+pub(crate) struct Source<'a> {
+    pub module: Option<&'a str>,
+    pub file: Option<&'a str>,
+    pub line: Option<u32>,
+    /// Length of the source prefix already formatted into
+    /// the message, which is removed when the source is displayed separately
+    pub prefix_len: usize,
+}
+
+/// Location in a file that a message refers to, for annotations
+#[derive(Default)]
+pub(crate) struct Annotation<'a> {
+    pub file: Option<&'a str>,
+    pub line: Option<u32>,
+    pub end_line: Option<u32>,
+    pub column: Option<u32>,
+    pub end_column: Option<u32>,
+    /// Short summary of the message
+    pub title: Option<&'a str>,
+}
+
 #[cfg(feature = "prompt")]
 struct PromptTask {
     send: OnceSend<cu::Result<Option<cu::ZString>>>,
@@ -321,27 +744,79 @@ impl PrintingThreadHandle {
     }
 }
 
+/// Animated lines (bars and prompts) that are displayed,
+/// i.e. need to be erased before printing anything else
+#[derive(Default)]
+struct AnimeLines {
+    /// Number of lines
+    lines: i32,
+    /// Display width of the animated bar lines, to calculate
+    /// how many rows they take after the terminal is resized
+    widths: Vec<usize>,
+    /// Terminal width when the bars are formatted
+    width: usize,
+}
+impl AnimeLines {
+    /// Format the codes to erase the lines, and reset
+    fn format_clear_codes(&mut self, buffer: &mut String) {
+        let width = fmt::term_width_or_max().max(1);
+        let resized = fmt::take_resized() || width != self.width;
+        self.format_clear_codes_at(buffer, width, resized);
+    }
+    fn format_clear_codes_at(&mut self, buffer: &mut String, width: usize, resized: bool) {
+        buffer.push_str("\r\x1b[K"); // erase the last spacing line
+        for _ in 0..self.rows(width, resized) {
+            buffer.push_str("\x1b[1A\x1b[K"); // move up one line and erase it
+        }
+        if resized {
+            // erase anything left below from the old layout
+            buffer.push_str("\x1b[J");
+        }
+        self.lines = 0;
+        self.widths.clear();
+    }
+    /// Get the number of rows the lines take on the terminal with the width
+    fn rows(&self, width: usize, resized: bool) -> i32 {
+        let mut rows = self.lines;
+        if resized {
+            // when the terminal gets narrower, lines printed at the old width
+            // are wrapped by the terminal, and take more rows
+            for w in &self.widths {
+                rows += w.div_ceil(width).saturating_sub(1) as i32;
+            }
+        }
+        rows
+    }
+}
+
 struct PrintingThread {
     /// main buffer to format outputs into
     buffer: String,
     /// temporary buffer to help formatting stuff
     temp: String,
-    /// track how many lines are animated
-    /// i.e. needs to be erased on the next cycle
-    lines: i32,
+    /// Last time the status of the bars are printed, when not animated
+    last_report: Instant,
+    /// Last progress sent to the terminal with OSC 9;4,
+    /// `Some(None)` for indeterminate
+    osc_progress: Option<Option<u8>>,
+    /// Last window title set, if enabled
+    title: Option<String>,
     /// animation tick counter
     tick: Tick,
 }
 
 impl PrintingThread {
     fn spawn() -> Option<JoinHandle<()>> {
+        fmt::listen_for_resize();
         std::thread::Builder::new()
             .name("cu-printer".to_string())
             .spawn(move || {
                 let mut state = PrintingThread {
                     buffer: String::new(),
                     temp: String::new(),
-                    lines: 0,
+                    last_report: Instant::now(),
+                    osc_progress: None,
+                    title: None,
                     tick: 0,
                 };
                 loop {
@@ -388,7 +863,7 @@ impl PrintingThread {
                 // with the prompt
                 if printer.anime_target.is_none() {
                     if !printer.buffered.is_empty() {
-                        printer.flush_buffered_to_stdout();
+                        printer.flush_buffered_messages();
                     }
                     // still print the prompt to stderr, but don't print control characters
                     let _ = write!(printer.stderr, "{}", task.prompt);
@@ -404,11 +879,9 @@ impl PrintingThread {
                 } else {
                     if !printer.buffered.is_empty() {
                         // clear previous bars and prompts
-                        self.format_clear_codes();
-                        // print buffered messages to stdout
-                        // this won't cause conflict with stderr
-                        // because animation will also print to stdout
-                        // if stdout is terminal
+                        self.format_clear_codes(printer);
+                        // print buffered messages, together with the
+                        // animation if they have the same target
                         self.flush_buffered(printer);
                         self.format_bars(printer);
                     }
@@ -438,7 +911,6 @@ impl PrintingThread {
                     if no_newline_after_prompt {
                         l = l.saturating_sub(1)
                     }
-                    self.lines += l;
 
                     // now we need to re-print the prompt above the progress bars
                     // by adding it to the buffer
@@ -451,6 +923,7 @@ impl PrintingThread {
                     // add the prompt to the print buffer
                     if let Ok(mut printer) = PRINTER.lock() {
                         if let Some(printer) = printer.as_mut() {
+                            printer.anime_lines.lines += l;
                             printer.buffered.push_str(&task.prompt);
                             printer.buffered.push('\n');
                         }
@@ -472,14 +945,25 @@ impl PrintingThread {
         let Some(printer) = printer_guard.as_mut() else {
             return ControlFlow::Break(());
         };
+        let result = self.format_frame(printer);
+        self.print_buffer_to_anime_target(printer);
+        result
+    }
+
+    /// Format the next frame of the animation to the buffer, or erase the bars
+    /// if they are all done. Returns `Break` if the thread should end
+    fn format_frame(&mut self, printer: &mut Printer) -> ControlFlow<()> {
         if printer.anime_target.is_some() {
             // clear previous bars and prompts
-            self.format_clear_codes();
+            self.format_clear_codes(printer);
             self.flush_buffered(printer);
             self.format_bars(printer);
-            self.print_buffer_to_anime_target(printer);
+            self.format_osc(printer);
         } else {
-            printer.flush_buffered_to_stdout();
+            // bars are only kept for reporting the status
+            printer.bars.retain(|bar| bar.strong_count() > 0);
+            self.report_progress_if_needed(printer);
+            printer.flush_buffered_messages();
         }
         let bars_empty = printer.bars.is_empty();
         #[cfg(feature = "prompt")]
@@ -489,8 +973,8 @@ impl PrintingThread {
 
         if bars_empty && printer.anime_target.is_some() {
             // erase the bars
-            self.format_clear_codes();
-            self.print_buffer_to_anime_target(printer);
+            self.format_clear_codes(printer);
+            self.format_osc_clear();
         }
 
         // check exit
@@ -500,7 +984,7 @@ impl PrintingThread {
             // whenever someone calls, even before this thread is done
             printer.print_task.mark_join();
             // we know the printer buffer is empty
-            // because we just printed all of it while having
+            // because we print all of it while having
             // the lock on the printer, no need to print again
             return ControlFlow::Break(());
         }
@@ -508,26 +992,39 @@ impl PrintingThread {
         ControlFlow::Continue(())
     }
 
+    /// Print the status of the bars, if they are not animated
+    /// and the progress interval has passed since the last report
+    fn report_progress_if_needed(&mut self, printer: &mut Printer) {
+        self.report_progress_at(printer, Instant::now(), lv::progress_interval());
+    }
+
+    /// Print the status of the bars at `now`, if the `interval` has passed since the
+    /// last report. A zero interval means the status is not printed
+    fn report_progress_at(&mut self, printer: &mut Printer, now: Instant, interval: Duration) {
+        if interval.is_zero() || !printer.should_report_progress() {
+            return;
+        }
+        if now.saturating_duration_since(self.last_report) >= interval {
+            self.last_report = now;
+            printer.print_progress_report(self.tick);
+        }
+    }
+
     fn format_bars(&mut self, printer: &mut Printer) {
         // remeasure terminal width on every cycle
         let width = fmt::term_width_or_max();
-        self.buffer.push_str(printer.colors.yellow);
-
-        // progress bar animation chars
-        #[rustfmt::skip]
-        const CHARS: [char; 30] = [
-            '\u{280b}', '\u{280b}', '\u{280b}', '\u{280b}', '\u{280b}',
-            '\u{2819}', '\u{2819}', '\u{2819}', '\u{2819}', '\u{2819}',
-            '\u{2838}', '\u{2838}', '\u{2838}', '\u{2838}', '\u{2838}',
-            '\u{2834}', '\u{2834}', '\u{2834}', '\u{2834}', '\u{2834}',
-            '\u{2826}', '\u{2826}', '\u{2826}', '\u{2826}', '\u{2826}',
-            '\u{2807}', '\u{2807}', '\u{2807}', '\u{2807}', '\u{2807}',
-        ];
-        let anime = CHARS[(self.tick as usize) % CHARS.len()];
+        printer.anime_lines.width = width;
+        self.buffer.push_str(printer.colors.progress);
+        let start = self.buffer.len();
+        let mut new_lines = 0;
+
+        // progress bar animation chars, each frame is displayed for 5 ticks
+        let spinner = fmt::glyphs().spinner;
+        let anime = spinner[(self.tick as usize / 5) % spinner.len()];
 
         let mut formatter = BarFormatter {
             colors: printer.colors,
-            bar_color: printer.colors.yellow,
+            bar_color: printer.colors.progress,
             width,
             tick: self.tick,
             now: &mut None,
@@ -543,59 +1040,96 @@ impl PrintingThread {
             if width >= 2 {
                 formatter.out.push(anime);
                 formatter.out.push(']');
-                self.lines += bar.format(&mut formatter);
+                new_lines += bar.format(&mut formatter);
             } else {
                 formatter.out.push('\n');
-                self.lines += 1;
+                new_lines += 1;
             }
 
             true
         });
+        printer.anime_lines.lines += new_lines;
+        printer.anime_lines.widths.extend(
+            self.buffer[start..]
+                .split('\n')
+                .take(new_lines as usize)
+                .map(fmt::ansi::width),
+        );
         self.buffer.push_str(printer.colors.reset);
     }
 
+    /// Format OSC codes to show the progress of the first bar in the
+    /// taskbar or tab, and set the window title, if enabled. Only the changes are sent
+    fn format_osc(&mut self, printer: &Printer) {
+        // OSC codes are only sent when the colors are enabled
+        if printer.colors.reset.is_empty() {
+            return;
+        }
+        let Some(bar) = printer.bars.iter().find_map(|x| x.upgrade()) else {
+            return;
+        };
+        if lv::is_taskbar_progress_enabled() {
+            let percentage = bar.percentage();
+            if self.osc_progress != Some(percentage) {
+                self.osc_progress = Some(percentage);
+                fmt::osc::push_progress(&mut self.buffer, percentage);
+            }
+        }
+        if !lv::is_window_title_enabled() {
+            return;
+        }
+        self.temp.clear();
+        bar.format_title(&mut self.temp);
+        match &mut self.title {
+            Some(title) if *title == self.temp => {}
+            Some(title) => {
+                title.clone_from(&self.temp);
+                fmt::osc::push_title(&mut self.buffer, title);
+            }
+            None => {
+                fmt::osc::push_title_save(&mut self.buffer);
+                fmt::osc::push_title(&mut self.buffer, &self.temp);
+                self.title = Some(self.temp.clone());
+            }
+        }
+    }
+
+    /// Format OSC codes to remove the progress and restore the window title
+    fn format_osc_clear(&mut self) {
+        if self.osc_progress.take().is_some() {
+            fmt::osc::push_progress_clear(&mut self.buffer);
+        }
+        if self.title.take().is_some() {
+            fmt::osc::push_title_restore(&mut self.buffer);
+        }
+    }
+
     /// Format codes to clear the animated part
     #[inline]
-    fn format_clear_codes(&mut self) {
-        self.buffer.push_str("\r\x1b[K"); // erase the last spacing line
-        for _ in 0..self.lines {
-            self.buffer.push_str("\x1b[1A\x1b[K"); // move up one line and erase it
-        }
-        self.lines = 0;
+    fn format_clear_codes(&mut self, printer: &mut Printer) {
+        printer.anime_lines.format_clear_codes(&mut self.buffer);
     }
 
     /// Print `buffer` to progress bar target
     fn print_buffer_to_anime_target(&mut self, printer: &mut Printer) {
-        use std::io::Write as _;
-        match printer.anime_target {
-            None => {}
-            Some(Target::Stdout) => {
-                let _ = write!(printer.stdout, "{}", self.buffer);
-                let _ = printer.stdout.flush();
-            }
-            Some(Target::Stderr) => {
-                let _ = write!(printer.stderr, "{}", self.buffer);
-                let _ = printer.stderr.flush();
-            }
+        if let Some(target) = printer.anime_target {
+            target.write(&mut printer.stdout, &mut printer.stderr, &self.buffer);
         }
         self.buffer.clear();
     }
 
     /// Flush the printer buffered messages
     fn flush_buffered(&mut self, printer: &mut Printer) {
-        match printer.anime_target {
-            // if the animation target is also stdout,
-            // it's important that we take the buffered messages
-            // into our own buffer, so we don't immediately
-            // flush stdout. this prevents flushing partial output,
-            // which cause the progress animation to flicker
-            Some(Target::Stdout) => {
-                self.buffer.push_str(&printer.buffered);
-                printer.buffered.clear();
-            }
-            _ => {
-                printer.flush_buffered_to_stdout();
-            }
+        // if the animation target is also the message target,
+        // it's important that we take the buffered messages
+        // into our own buffer, so we don't immediately
+        // flush the stream. this prevents flushing partial output,
+        // which cause the progress animation to flicker
+        if printer.anime_target == Some(printer.message_target) {
+            self.buffer.push_str(&printer.buffered);
+            printer.buffered.clear();
+        } else {
+            printer.flush_buffered_messages();
         }
     }
 }
@@ -607,6 +1141,21 @@ enum Target {
     /// Print to Stderr
     Stderr,
 }
+impl Target {
+    fn write(self, stdout: &mut io::Stdout, stderr: &mut io::Stderr, text: &str) {
+        use std::io::Write as _;
+        match self {
+            Self::Stdout => {
+                let _ = write!(stdout, "{text}");
+                let _ = stdout.flush();
+            }
+            Self::Stderr => {
+                let _ = write!(stderr, "{text}");
+                let _ = stderr.flush();
+            }
+        }
+    }
+}
 #[cfg(feature = "prompt")]
 fn read_prompt(task: &PromptTask) -> cu::Result<Option<cu::ZString>> {
     let is_password = task.is_password;
@@ -622,3 +1171,169 @@ fn read_prompt(task: &PromptTask) -> cu::Result<Option<cu::ZString>> {
         Some(Some(x)) => Ok(Some(x)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_bar_done_is_logged_when_animated() {
+        let path = std::env::temp_dir().join(format!("cu-printer-test-{}.log", std::process::id()));
+        let mut printer = Printer::new(false, lv::Format::Text, &Theme::default());
+        printer.anime_target = Some(Target::Stdout);
+        printer.log_file = Some(LogFile::open(&path).unwrap());
+        printer.print_bar_done(&BarResult::Done("child finished".to_string()), false);
+        drop(printer);
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(content.contains("child finished"), "{content}");
+    }
+
+    fn anime_lines(widths: &[usize], width: usize) -> AnimeLines {
+        AnimeLines {
+            lines: widths.len() as i32,
+            widths: widths.to_vec(),
+            width,
+        }
+    }
+
+    #[test]
+    fn test_clear_rows() {
+        let lines = anime_lines(&[60, 20, 45], 60);
+        assert_eq!(lines.rows(60, false), 3);
+        // narrower: the long lines are wrapped
+        assert_eq!(lines.rows(40, true), 5);
+        assert_eq!(lines.rows(20, true), 7);
+        assert_eq!(lines.rows(1, true), 125);
+        // wider: lines are not re-flowed
+        assert_eq!(lines.rows(80, true), 3);
+        // a prompt line is counted without width
+        let mut lines = anime_lines(&[60], 60);
+        lines.lines += 1;
+        assert_eq!(lines.rows(30, true), 3);
+    }
+
+    #[test]
+    fn test_clear_codes() {
+        let mut buffer = String::new();
+        let mut lines = anime_lines(&[60, 20], 60);
+        lines.format_clear_codes_at(&mut buffer, 60, false);
+        assert_eq!(buffer, "\r\x1b[K\x1b[1A\x1b[K\x1b[1A\x1b[K");
+        assert_eq!(lines.lines, 0);
+        assert!(lines.widths.is_empty());
+
+        buffer.clear();
+        let mut lines = anime_lines(&[60, 20], 60);
+        lines.format_clear_codes_at(&mut buffer, 30, true);
+        assert_eq!(
+            buffer,
+            "\r\x1b[K\x1b[1A\x1b[K\x1b[1A\x1b[K\x1b[1A\x1b[K\x1b[J"
+        );
+    }
+
+    #[test]
+    fn test_progress_report_interval() {
+        let interval = Duration::from_secs(30);
+        let mut printer = Printer::new(false, lv::Format::Text, &Theme::default());
+        printer.anime_target = None;
+        let bar = crate::progress("building").total(10).eta(false).spawn();
+        crate::progress!(bar = 3);
+        printer.bars.push(Arc::downgrade(&bar));
+        let start = Instant::now();
+        let mut thread = PrintingThread {
+            buffer: String::new(),
+            temp: String::new(),
+            last_report: start,
+            osc_progress: None,
+            title: None,
+            tick: 0,
+        };
+        thread.report_progress_at(&mut printer, start + interval / 2, interval);
+        assert_eq!(printer.buffered, "");
+        thread.report_progress_at(&mut printer, start + interval, interval);
+        let report = std::mem::take(&mut printer.buffered);
+        assert!(report.contains("[3/10] building"), "{report}");
+        assert_eq!(report.lines().count(), 1);
+        // not printed again until the interval passes
+        thread.report_progress_at(&mut printer, start + interval * 3 / 2, interval);
+        assert_eq!(printer.buffered, "");
+        thread.report_progress_at(&mut printer, start + interval * 2, interval);
+        assert!(std::mem::take(&mut printer.buffered).contains("[3/10] building"));
+        // zero interval disables the report
+        thread.report_progress_at(&mut printer, start + interval * 10, Duration::ZERO);
+        assert_eq!(printer.buffered, "");
+        bar.done();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_stop_for_panic_erases_bars() {
+        let mut printer = Printer::new(false, lv::Format::Text, &Theme::default());
+        printer.anime_target = Some(Target::Stderr);
+        printer.anime_lines = AnimeLines {
+            lines: 2,
+            widths: vec![10, 10],
+            width: fmt::term_width_or_max(),
+        };
+        let bar = crate::progress("building").total(10).spawn();
+        printer.bars.push(Arc::downgrade(&bar));
+        let _ = printer.stop_for_panic();
+        let mut thread = PrintingThread {
+            buffer: String::new(),
+            temp: String::new(),
+            last_report: Instant::now(),
+            osc_progress: None,
+            title: None,
+            tick: 0,
+        };
+        // the printing thread erases the bars and ends, before
+        // the panic message is printed
+        assert!(thread.format_frame(&mut printer).is_break());
+        assert!(
+            thread
+                .buffer
+                .starts_with("\r\x1b[K\x1b[1A\x1b[K\x1b[1A\x1b[K"),
+            "{:?}",
+            thread.buffer
+        );
+        assert!(!thread.buffer.contains("building"));
+        assert_eq!(printer.anime_lines.lines, 0);
+        assert!(!printer.print_task.active());
+        bar.done();
+    }
+
+    #[test]
+    fn test_output_erases_bars_on_stdout() {
+        let mut printer = Printer::new(false, lv::Format::Text, &Theme::default());
+        printer.anime_target = Some(Target::Stdout);
+        printer.anime_lines.lines = 2;
+        printer.anime_lines.widths = vec![10, 10];
+        printer.print_output("data\n");
+        assert_eq!(printer.anime_lines.lines, 0);
+        assert!(printer.anime_lines.widths.is_empty());
+        assert!(printer.anime_target != Some(Target::Stdout));
+    }
+
+    #[cfg(feature = "process")]
+    #[test]
+    fn test_pager_quit_restores_printing() {
+        let mut printer = Printer::new(false, lv::Format::Text, &Theme::default());
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        printer.start_pager(sender);
+        printer.print_output("to the pager\n");
+        assert_eq!(receiver.len(), 1);
+        // the user quits the pager
+        drop(receiver);
+        printer.print_output("after the pager\n");
+        assert!(printer.pager.is_none());
+    }
+
+    #[cfg(feature = "prompt")]
+    #[test]
+    fn test_json_prompt_has_no_text_prompt() {
+        let mut printer = Printer::new(false, lv::Format::Json, &Theme::default());
+        let _recv = printer.show_prompt("continue?", false);
+        assert_eq!(printer.pending_prompts.len(), 1);
+        assert_eq!(printer.pending_prompts[0].prompt, "");
+    }
+}
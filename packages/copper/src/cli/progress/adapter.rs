@@ -0,0 +1,364 @@
+use std::sync::Arc;
+
+use crate::cli::progress::{ProgressBar, ProgressBarBuilder};
+
+/// Extension to drive a progress bar with an iterator
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::pre::*;
+/// for i in (0..10).progress("doing something") {
+///     cu::debug!("doing {i}th step");
+/// }
+/// ```
+pub trait ProgressIterExtension: Iterator + Sized {
+    /// Spawn a progress bar with the message and update it for
+    /// each item. See [`ProgressIter`]
+    fn progress(self, message: impl Into<String>) -> ProgressIter<Self> {
+        self.progress_with(crate::progress(message))
+    }
+
+    /// Spawn a progress bar from the builder and update it for
+    /// each item. See [`ProgressIter`]
+    ///
+    /// ```rust
+    /// # use pistonite_cu as cu;
+    /// use cu::pre::*;
+    /// let builder = cu::progress("doing something").keep(false).eta(false);
+    /// for i in (0..10).progress_with(builder) {
+    ///     cu::debug!("doing {i}th step");
+    /// }
+    /// ```
+    fn progress_with(self, builder: ProgressBarBuilder) -> ProgressIter<Self> {
+        let total = match self.size_hint() {
+            (lower, Some(upper)) if lower == upper => lower,
+            // not known
+            _ => 0,
+        };
+        let bar = builder.total_if_unset(total).spawn();
+        ProgressIter::new(self, bar)
+    }
+}
+impl<I: Iterator> ProgressIterExtension for I {}
+
+/// Iterator that updates the progress bar with the number of items
+/// yielded so far.
+///
+/// When spawned with [`progress`](ProgressIterExtension::progress), the total
+/// is set from `size_hint()` if it is exact, otherwise the total is unknown.
+/// The bar is marked as done when the iterator is exhausted, with the total
+/// set to the actual number of items. Dropping the iterator before
+/// it is exhausted leaves the bar interrupted.
+///
+/// Use [`bar`](Self::bar) to update the message while iterating:
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::pre::*;
+/// let mut iter = ["a", "b", "c"].into_iter().progress("processing");
+/// let bar = iter.bar().clone();
+/// for name in iter {
+///     cu::progress!(bar, "processing {name}");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProgressIter<I> {
+    iter: I,
+    bar: Arc<ProgressBar>,
+    current: u64,
+}
+
+impl<I: Iterator> ProgressIter<I> {
+    /// Update an existing progress bar with the iterator. The total of the
+    /// bar is not changed.
+    pub fn new(iter: I, bar: Arc<ProgressBar>) -> Self {
+        Self {
+            iter,
+            bar,
+            current: 0,
+        }
+    }
+
+    /// Get the progress bar being updated
+    #[inline(always)]
+    pub fn bar(&self) -> &Arc<ProgressBar> {
+        &self.bar
+    }
+}
+
+impl<I: Iterator> Iterator for ProgressIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(item) => {
+                self.current += 1;
+                self.bar.__set(self.current, None);
+                Some(item)
+            }
+            None => {
+                self.bar.finish();
+                None
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Async stream that updates the progress bar with the number of items
+/// yielded so far. This is the async version of [`ProgressIter`].
+///
+/// There is no `futures` dependency, so this cannot wrap a `futures::Stream`
+/// directly, and does not implement it either. Instead, the stream is created
+/// from a poll function, such as `poll_recv` of tokio channels, and items
+/// are polled with [`next`](Self::next), or [`poll_next`](Self::poll_next)
+/// to implement `Stream` on top of it.
+///
+/// The total is not set by the stream, since it is not known ahead of time.
+/// Spawn the bar with a total of `0` to show the count if the total is unknown.
+/// The bar is marked as done when the stream is exhausted, with the total
+/// set to the actual number of items. Dropping the
+/// stream before that leaves the bar interrupted.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+/// for i in 0..10 {
+///     send.send(i).unwrap();
+/// }
+/// drop(send);
+/// let bar = cu::progress("receiving").total(10).spawn();
+/// let mut stream = cu::cli::ProgressStream::new(move |cx| recv.poll_recv(cx), bar);
+/// cu::co::run(async move {
+///     while let Some(i) = stream.next().await {
+///         cu::debug!("received {i}");
+///     }
+/// });
+/// ```
+#[cfg(feature = "coroutine")]
+pub struct ProgressStream<S> {
+    poll_next: S,
+    bar: Arc<ProgressBar>,
+    current: u64,
+}
+
+#[cfg(feature = "coroutine")]
+impl<T, S> ProgressStream<S>
+where
+    S: FnMut(&mut std::task::Context<'_>) -> std::task::Poll<Option<T>>,
+{
+    /// Update the progress bar with the items polled from `poll_next`. The
+    /// total of the bar is not changed.
+    pub fn new(poll_next: S, bar: Arc<ProgressBar>) -> Self {
+        Self {
+            poll_next,
+            bar,
+            current: 0,
+        }
+    }
+
+    /// Get the progress bar being updated
+    #[inline(always)]
+    pub fn bar(&self) -> &Arc<ProgressBar> {
+        &self.bar
+    }
+
+    /// Wait for the next item, or `None` if the stream is exhausted
+    pub async fn next(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    /// Poll the next item, and update the progress bar if it's ready
+    pub fn poll_next(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<T>> {
+        let result = (self.poll_next)(cx);
+        match &result {
+            std::task::Poll::Ready(Some(_)) => {
+                self.current += 1;
+                self.bar.__set(self.current, None);
+            }
+            std::task::Poll::Ready(None) => self.bar.finish(),
+            std::task::Poll::Pending => {}
+        }
+        result
+    }
+}
+
+/// Reader that updates the progress bar with the number of bytes read.
+///
+/// The total is not set by the reader, typically the bar should be spawned
+/// with [`total_bytes`](ProgressBarBuilder::total_bytes) if the size is known.
+/// The bar is marked as done when the reader reaches EOF.
+///
+/// This works with both `std::io::Read`, and `tokio::io::AsyncRead` (with the
+/// `coroutine` feature), so it can be used with [`cu::copy`](crate::copy)
+/// and [`cu::co_copy`](crate::co_copy) to show the progress of copying.
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// # fn main() -> cu::Result<()> {
+/// let file = std::fs::File::open("large.bin")?;
+/// let bar = cu::progress("copying")
+///     .total_bytes(file.metadata()?.len())
+///     .spawn();
+/// let mut reader = cu::ProgressReader::new(file, bar);
+/// cu::copy(&mut reader, &mut std::io::sink())?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct ProgressReader<R> {
+    inner: R,
+    bar: Arc<ProgressBar>,
+}
+
+impl<R> ProgressReader<R> {
+    /// Update the progress bar when reading from the reader
+    pub fn new(reader: R, bar: Arc<ProgressBar>) -> Self {
+        Self { inner: reader, bar }
+    }
+
+    /// Get the progress bar being updated
+    #[inline(always)]
+    pub fn bar(&self) -> &Arc<ProgressBar> {
+        &self.bar
+    }
+
+    /// Get the inner reader. The progress bar is dropped, and will
+    /// be interrupted if the reader has not reached EOF
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.bar.finish();
+        } else {
+            self.bar.__inc(n as u64, None);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "coroutine")]
+impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let has_space = buf.remaining() != 0;
+        let result = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let std::task::Poll::Ready(Ok(())) = &result {
+            let n = buf.filled().len() - before;
+            if n == 0 && has_space {
+                self.bar.finish();
+            } else {
+                self.bar.__inc(n as u64, None);
+            }
+        }
+        result
+    }
+}
+
+/// Writer that updates the progress bar with the number of bytes written.
+///
+/// The total is not set by the writer, typically the bar should be spawned
+/// with [`total_bytes`](ProgressBarBuilder::total_bytes) if the size is known.
+/// Since the writer cannot know when the writing is complete, call
+/// [`finish`](Self::finish) to mark the bar as done, if the total is not
+/// known ahead of time.
+///
+/// This works with both `std::io::Write`, and `tokio::io::AsyncWrite` (with the
+/// `coroutine` feature).
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// use std::io::Write as _;
+/// # fn main() -> cu::Result<()> {
+/// let bar = cu::progress("writing").total_bytes(0).spawn();
+/// let file = std::fs::File::create("output.bin")?;
+/// let mut writer = cu::ProgressWriter::new(file, bar);
+/// writer.write_all(&[0; 1000])?;
+/// let file = writer.finish();
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct ProgressWriter<W> {
+    inner: W,
+    bar: Arc<ProgressBar>,
+}
+
+impl<W> ProgressWriter<W> {
+    /// Update the progress bar when writing to the writer
+    pub fn new(writer: W, bar: Arc<ProgressBar>) -> Self {
+        Self { inner: writer, bar }
+    }
+
+    /// Get the progress bar being updated
+    #[inline(always)]
+    pub fn bar(&self) -> &Arc<ProgressBar> {
+        &self.bar
+    }
+
+    /// Mark the progress bar as done, and get the inner writer
+    pub fn finish(self) -> W {
+        self.bar.finish();
+        self.inner
+    }
+
+    /// Get the inner writer. The progress bar is dropped, and will be
+    /// interrupted if the total is not reached
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bar.__inc(n as u64, None);
+        Ok(n)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "coroutine")]
+impl<W: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for ProgressWriter<W> {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let result = std::pin::Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(n)) = &result {
+            self.bar.__inc(*n as u64, None);
+        }
+        result
+    }
+
+    #[inline(always)]
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    #[inline(always)]
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use crate::cli::progress::{Estimater, ProgressBar, State, StateImmut};
+use crate::cli::progress::{
+    Component, Estimater, ProgressBar, ProgressTemplate, Rate, State, StateImmut,
+};
 
 /// Builder for a progress bar
 #[derive(Debug, Clone)] // Clone sometimes needed to build by ref.. without unsafe
@@ -17,6 +19,8 @@ pub struct ProgressBarBuilder {
     show_eta: bool,
     /// If percentage should be visible (only effective if total is finite)
     show_percentage: bool,
+    /// If rate should be visible (only effective if not unbounded)
+    show_rate: bool,
     /// Message to display after done, instead of the default
     done_message: Option<String>,
     /// Message to display if the bar is interrupted
@@ -25,6 +29,8 @@ pub struct ProgressBarBuilder {
     max_display_children: usize,
     /// Optional parent of the bar
     parent: Option<Arc<ProgressBar>>,
+    /// Custom layout of the bar
+    template: Option<ProgressTemplate>,
 }
 
 impl ProgressBarBuilder {
@@ -37,10 +43,12 @@ impl ProgressBarBuilder {
             keep: None,
             show_eta: true,
             show_percentage: true,
+            show_rate: false,
             done_message: None,
             interrupted_message: None,
             max_display_children: usize::MAX / 2,
             parent: None,
+            template: None,
         }
     }
     /// Set the total steps. `0` means total is unknown, which can be set
@@ -80,6 +88,14 @@ impl ProgressBarBuilder {
         self
     }
 
+    /// Set the total steps if not already set, used by the adapters
+    pub(crate) fn total_if_unset(mut self, total: usize) -> Self {
+        if self.total.is_none() {
+            self.total = Some(total as u64);
+        }
+        self
+    }
+
     /// Set if the progress bar should be kept in the output
     /// after it's done.
     ///
@@ -124,6 +140,23 @@ impl ProgressBarBuilder {
         self
     }
 
+    /// Set if the rate (i.e. throughput) should be displayed, as steps per second,
+    /// or bytes per second if the total is set with [`total_bytes`](Self::total_bytes).
+    /// Only effective if not unbounded. Default is `false`
+    ///
+    /// The rate is smoothed over the recent updates. The average rate over
+    /// the whole progress is included in the done message.
+    ///
+    /// ```rust
+    /// # use pistonite_cu as cu;
+    /// cu::progress("downloading").total_bytes(1000000).rate(true);
+    /// ```
+    #[inline(always)]
+    pub fn rate(mut self, show: bool) -> Self {
+        self.show_rate = show;
+        self
+    }
+
     /// Set a message to be displayed when the progress is done.
     /// Requires `keep(true)` - which is the default, but
     /// `when_done` will not automatically turn it on for you.
@@ -166,6 +199,25 @@ impl ProgressBarBuilder {
         self
     }
 
+    /// Set a custom layout for the bar while it's running,
+    /// see [`ProgressTemplate`] for the syntax. The done and interrupted
+    /// messages are not affected.
+    ///
+    /// The ETA and rate are always calculated if they are used in the template,
+    /// regardless of [`eta`](Self::eta) and [`rate`](Self::rate)
+    ///
+    /// ```rust
+    /// # use pistonite_cu as cu;
+    /// # fn main() -> cu::Result<()> {
+    /// let template = cu::cli::ProgressTemplate::new("{prefix} [{bar}] {bytes} {rate}")?;
+    /// cu::progress("downloading").total_bytes(1000000).template(template);
+    /// # Ok(()) }
+    /// ```
+    pub fn template(mut self, template: ProgressTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
     /// Set the parent progress bar.
     ///
     /// If the parent is known to be `Some`, use `parent.child(...)` instead
@@ -199,11 +251,16 @@ impl ProgressBarBuilder {
             unbounded: self.total.is_none(),
             display_bytes: self.total_is_in_bytes,
             max_display_children: self.max_display_children,
+            template: self.template,
         };
-        let eta = self.show_eta.then(Estimater::new);
+        let template = state_immut.template.as_ref();
+        let uses = |component| template.is_some_and(|x| x.uses(component));
+        let eta = (self.show_eta || uses(Component::Eta)).then(Estimater::new);
+        let rate = (self.show_rate || uses(Component::Rate)).then(Rate::new);
         let state = State::new(
             self.total.unwrap_or(0),
             eta,
+            rate,
             done_message,
             self.interrupted_message,
         );
@@ -72,3 +72,65 @@ impl Estimater {
         }
     }
 }
+
+/// Number of ticks between each sample of the rate
+const RATE_SAMPLE_TICKS: Tick = 20;
+/// Weight of the new sample in the smoothed rate
+const RATE_SMOOTHING: f32 = 0.25;
+
+/// Estimate the rate (steps per second) for progress bar
+#[derive(Debug)]
+pub struct Rate {
+    /// Time when the progress started, for the average rate
+    start: Instant,
+    /// Step number when we last sampled the rate
+    last_step: u64,
+    /// Tick number when we last sampled the rate
+    last_tick: Option<Tick>,
+    /// Smoothed rate, in steps per second
+    smoothed: Option<f32>,
+}
+
+impl Rate {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_step: 0,
+            last_tick: None,
+            smoothed: None,
+        }
+    }
+
+    /// Update with the current step and get the smoothed rate.
+    /// None if there is not enough data yet
+    pub fn update(&mut self, current: u64, tick: Tick) -> Option<f32> {
+        let Some(last_tick) = self.last_tick else {
+            self.last_step = current;
+            self.last_tick = Some(tick);
+            return None;
+        };
+        let ticks = tick.wrapping_sub(last_tick);
+        if ticks < RATE_SAMPLE_TICKS {
+            return self.smoothed;
+        }
+        let elapsed = (TICK_INTERVAL * ticks).as_secs_f32();
+        let sample = current.saturating_sub(self.last_step) as f32 / elapsed;
+        self.last_step = current;
+        self.last_tick = Some(tick);
+        let rate = match self.smoothed {
+            None => sample,
+            Some(x) => x + (sample - x) * RATE_SMOOTHING,
+        };
+        self.smoothed = Some(rate);
+        Some(rate)
+    }
+
+    /// Get the average rate since the progress started
+    pub fn average(&self, current: u64) -> f32 {
+        let elapsed = self.start.elapsed().as_secs_f32();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        current as f32 / elapsed
+    }
+}
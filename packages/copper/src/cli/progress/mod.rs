@@ -12,6 +12,8 @@
 /// - Prefix: A string configured once when launching the progress bar
 /// - Percentage: Percentage display for the current and total steps, For example `42.00%`.
 ///   This can be turned off if not needed
+/// - Rate: The throughput, for example `12.5/s`, or `1.2M/s` for bytes. This is off by default,
+///   and can be turned on with `.rate(true)`
 /// - ETA: Estimated remaining time. This can be turned off if not needed
 /// - Message: A message that can be set while the progress bar is showing. For example,
 ///   this can be the name of the current file being processed, etc.
@@ -34,7 +36,9 @@
 /// Each progress bar can have 3 states: `progress`, `done`, and `interrupted`.
 ///
 /// When in `progress`, the bar will be animated if the output is a terminal. Otherwise,
-/// updates will be ignored.
+/// the status of each root bar is printed in one line every 30 seconds, so there is
+/// still output in logs (for example, in CI). The interval can be changed with
+/// [`cu::lv::set_progress_interval`](crate::lv::set_progress_interval).
 ///
 /// The bar will be `done` when all handles are dropped if 1 of the following is true:
 /// - The bar has finite total, and current step equals total step
@@ -82,6 +86,23 @@
 /// drop(bar);
 /// ```
 ///
+/// ## Adapters
+/// The bar can also be updated automatically by wrapping an iterator, reader or writer.
+/// The adapters mark the bar as done when the iterator or reader is exhausted.
+/// - [`ProgressIterExtension`] (in the prelude) adds `.progress(message)` to iterators,
+///   with the total set from `size_hint()`
+/// - [`ProgressReader`] and [`ProgressWriter`] count the bytes read or written, and also work
+///   as async readers and writers with the `coroutine` feature
+/// - [`ProgressStream`] counts the items of an async stream (with the `coroutine` feature)
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::pre::*;
+/// for i in (0..10).progress("doing something") {
+///     cu::debug!("doing {i}th step");
+/// }
+/// ```
+///
 /// ## Building the bar
 /// This function `cu::progress` will make a [`ProgressBarBuilder`]
 /// with these default configs:
@@ -93,9 +114,29 @@
 ///
 /// See [`ProgressBarBuilder`] for builder methods
 ///
+/// ## Templates
+/// The layout of the components can be customized with a [`ProgressTemplate`],
+/// for example, to show a graphical bar or the elapsed time:
+/// ```rust
+/// # use pistonite_cu as cu;
+/// # fn main() -> cu::Result<()> {
+/// let template = cu::cli::ProgressTemplate::new("{prefix} [{bar}] {steps} {elapsed}")?;
+/// let bar = cu::progress("building").total(10).template(template).spawn();
+/// # Ok(()) }
+/// ```
+///
 /// ## Print Levels
 /// The bar final messages are suppressed at `-q` and the bar animations are suppressed at `-qq`
 ///
+/// ## Terminal Integration
+/// When the bars are animated and colors are enabled, the progress of the first
+/// root bar can also be sent to the terminal (OSC 9;4) to show it in the tab or taskbar.
+/// This is enabled automatically in terminals known to support it, like Windows Terminal
+/// and WezTerm, or by calling
+/// [`cu::lv::enable_taskbar_progress`](crate::lv::enable_taskbar_progress). Call
+/// [`cu::lv::enable_window_title`](crate::lv::enable_window_title) to also set the window
+/// title to the prefix and message of the bar.
+///
 /// ## Other considerations
 /// If the progress bar print section exceeds the terminal height,
 /// it will probably not render properly. Keep in mind when you
@@ -110,7 +151,7 @@ pub fn progress(message: impl Into<String>) -> ProgressBarBuilder {
 
 mod eta;
 
-pub use eta::Estimater;
+pub use eta::{Estimater, Rate};
 mod state;
 pub use state::ProgressBar;
 use state::{State, StateImmut};
@@ -119,37 +160,14 @@ pub use builder::ProgressBarBuilder;
 mod util;
 pub use util::{BarFormatter, BarResult};
 use util::{ChildState, ChildStateStrong};
+mod adapter;
 mod macros;
+#[cfg(feature = "coroutine")]
+pub use adapter::ProgressStream;
+pub use adapter::{ProgressIter, ProgressIterExtension, ProgressReader, ProgressWriter};
+mod template;
+pub use template::ProgressTemplate;
+use template::{Component, TemplateValues};
 
 /// Convenience type for `Arc<cu::ProgressBar>`
 pub type ProgressHandle = std::sync::Arc<ProgressBar>;
-
-// spawn_iter stuff, keep for reference, not sure if needed yet
-// .enumerate seems more readable
-/*
-/// In the example above, you can also attach it to an iterator directly.
-/// The builder will call `size_hint()` once and set the total on the bar,
-/// and will automatically mark it as done if `next()` returns `None`.
-///
-/// If the default iteration behavior of `spawn_iter` is not desirable, use `spawn`
-/// and iterate manually.
-/// ```rust
-/// # use pistonite_cu as cu;
-/// for i in cu::progress("doing something").spawn_iter(0..10) {
-///     cu::print!("doing {i}th step");
-/// }
-/// ```
-///
-/// Note that in the code above, we didn't have a handle to the bar directly
-/// to update the message, we can fix that by getting the bar from the iter
-///
-/// ```rust
-/// # use pistonite_cu as cu;
-/// let mut iter = cu::progress("doing something").spawn_iter(0..10);
-/// let bar = iter.bar();
-/// for i in iter {
-///     // bar = i is handled by the iterator automatically
-///     cu::progress!(bar, "doing {i}th step");
-/// }
-/// ```
-*/
@@ -2,16 +2,13 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use crate::cli::Tick;
-use crate::cli::fmt::ansi;
+use crate::cli::fmt::{ansi, glyphs};
 use crate::cli::printer::PRINTER;
 use crate::cli::progress::{
     BarFormatter, BarResult, ChildState, ChildStateStrong, Estimater, ProgressBarBuilder,
+    ProgressTemplate, Rate, TemplateValues,
 };
 
-const CHAR_BAR_TICK: char = '\u{251C}'; // |>
-const CHAR_BAR: char = '\u{2502}'; // |
-const CHAR_TICK: char = '\u{2514}'; // >
-
 /// Handle for a progress bar (This is the internal state, the handle is `Arc<ProgressBar>`)
 ///
 /// See [Progress Bars](fn@crate::progress)
@@ -145,12 +142,55 @@ impl ProgressBar {
         }
     }
 
+    /// Mark the bar as done with the current steps as the total,
+    /// used when the actual total is only known at the end
+    pub(crate) fn finish(&self) {
+        if let Ok(mut bar) = self.state_mut.lock() {
+            if bar.unreal_current != 0 {
+                bar.unreal_total = bar.unreal_current;
+            }
+            bar.finished = true;
+        }
+    }
+
     /// Format the bar
     #[inline(always)]
     pub(crate) fn format(&self, fmt: &mut BarFormatter<'_, '_, '_>) -> i32 {
         self.format_at_depth(0, &mut String::new(), fmt)
     }
 
+    /// Format the current status of the bar in one line, without
+    /// the children, for reporting progress when the bar is not animated
+    pub(crate) fn format_status(&self, fmt: &mut BarFormatter<'_, '_, '_>) {
+        let Ok(mut bar) = self.state_mut.lock() else {
+            return;
+        };
+        bar.format_self(fmt, fmt.width, &self.state);
+    }
+
+    /// Get the percentage (0-100) of the bar, `None` if the total is not known
+    pub(crate) fn percentage(&self) -> Option<u8> {
+        let bar = self.state_mut.lock().ok()?;
+        let (current, total) = bar.real_current_total(self.state.unbounded);
+        let total = total?;
+        Some((current.saturating_mul(100) / total).min(100) as u8)
+    }
+
+    /// Format the prefix and message of the bar, used as the window title
+    pub(crate) fn format_title(&self, out: &mut String) {
+        out.push_str(&self.state.prefix);
+        let Ok(bar) = self.state_mut.lock() else {
+            return;
+        };
+        if bar.message.is_empty() {
+            return;
+        }
+        if !self.state.prefix.is_empty() {
+            out.push_str(": ");
+        }
+        out.push_str(&bar.message);
+    }
+
     /// Format the bar at depth
     fn format_at_depth(
         &self,
@@ -212,6 +252,8 @@ pub struct StateImmut {
     /// Max number of children to display,
     /// children after the limit will only display one line "... and X more"
     pub max_display_children: usize,
+    /// Custom layout of the bar
+    pub template: Option<ProgressTemplate>,
 }
 
 /// Internal mutable state
@@ -226,12 +268,18 @@ pub struct State {
     /// None means use the default
     interrupted_message: Option<String>,
     eta: Option<Estimater>,
+    rate: Option<Rate>,
+    /// Marked as done, regardless of the current steps
+    finished: bool,
+    /// When the bar is spawned
+    start: Instant,
     children: Vec<ChildState>,
 }
 impl State {
     pub fn new(
         total: u64,
         eta: Option<Estimater>,
+        rate: Option<Rate>,
         done_message: Option<String>,
         interrupted_message: Option<String>,
     ) -> Self {
@@ -242,6 +290,9 @@ impl State {
             done_message,
             interrupted_message,
             eta,
+            rate,
+            finished: false,
+            start: Instant::now(),
             children: vec![],
         }
     }
@@ -263,6 +314,17 @@ impl State {
         )
     }
     #[inline(always)]
+    fn estimate_rate(&mut self, unbounded: bool, tick: Tick) -> Option<f32> {
+        if unbounded {
+            return None;
+        }
+        let current = match self.unreal_total {
+            0 => self.unreal_current,
+            total => self.unreal_current.min(total),
+        };
+        self.rate.as_mut()?.update(current, tick)
+    }
+    #[inline(always)]
     fn real_current_total(&self, unbounded: bool) -> (u64, Option<u64>) {
         if unbounded {
             (0, None)
@@ -305,8 +367,9 @@ impl State {
     }
 
     pub fn check_result(&self, state: &StateImmut) -> BarResult {
-        let is_interrupted = (self.unreal_current == 0 && self.unreal_total == 0)
-            || (self.unreal_current < self.unreal_total);
+        let is_interrupted = !self.finished
+            && ((self.unreal_current == 0 && self.unreal_total == 0)
+                || (self.unreal_current < self.unreal_total));
         if !is_interrupted {
             match &self.done_message {
                 None => BarResult::DontKeep,
@@ -388,6 +451,7 @@ impl State {
         fmt: &mut BarFormatter<'_, '_, '_>,
         state: &StateImmut,
     ) -> i32 {
+        let glyphs = glyphs();
         self.format_self(fmt, fmt.width.saturating_sub((depth + 1) * 2), state);
         fmt.out.push('\n');
         let mut lines = 1;
@@ -407,28 +471,28 @@ impl State {
             }
             // format the multi-line syntax
             out.push_str(". ");
-            out.push_str(fmt.colors.gray);
+            out.push_str(fmt.colors.tree);
             out.push_str(hierarchy);
             if i == children_count - 1 {
-                out.push(CHAR_TICK);
+                out.push(glyphs.tick);
                 hierarchy.push_str("  ");
             } else {
-                out.push(CHAR_BAR_TICK);
-                hierarchy.push(CHAR_BAR);
+                out.push(glyphs.bar_tick);
+                hierarchy.push(glyphs.bar);
                 hierarchy.push(' ');
             }
             out.push(' ');
             let width = fmt.width.saturating_sub((depth + 2) * 2);
             match child {
                 ChildStateStrong::Done(message) => {
-                    out.push_str(fmt.colors.green);
+                    out.push_str(fmt.colors.progress_done);
                     format_message_with_width(out, width, message);
                     out.push('\n');
                     lines += 1;
                     out.push_str(fmt.bar_color);
                 }
                 ChildStateStrong::Interrupted(message) => {
-                    out.push_str(fmt.colors.yellow);
+                    out.push_str(fmt.colors.progress_interrupted);
                     format_message_with_width(out, width, message);
                     out.push('\n');
                     lines += 1;
@@ -449,12 +513,12 @@ impl State {
             // display the ... and more line
             let out = &mut *fmt.out;
             out.push_str("| ");
-            out.push_str(fmt.colors.gray);
+            out.push_str(fmt.colors.tree);
             for _ in 0..depth {
-                out.push(CHAR_BAR);
+                out.push(glyphs.bar);
                 out.push(' ');
             }
-            out.push(CHAR_TICK);
+            out.push(glyphs.tick);
             out.push_str(fmt.colors.reset);
             use std::fmt::Write as _;
             let _ = write!(
@@ -476,6 +540,10 @@ impl State {
         mut width: usize,
         state: &StateImmut,
     ) {
+        if let Some(template) = &state.template {
+            self.format_template(template, fmt, width, state);
+            return;
+        }
         use std::fmt::Write as _;
         let out = &mut *fmt.out;
         let temp = &mut *fmt.temp;
@@ -509,10 +577,13 @@ impl State {
         let show_percentage = state.show_percentage && total.is_some();
         let eta = self.estimate_remaining(state.unbounded, fmt.now, fmt.tick);
         let show_eta = eta.is_some();
+        let rate = self.estimate_rate(state.unbounded, fmt.tick);
+        let show_rate = rate.is_some();
         let show_message = !self.message.is_empty();
 
         struct Spacing {
             show_separator: bool,
+            show_space_before_rate: bool,
             show_space_before_eta: bool,
             show_space_before_message: bool,
         }
@@ -520,15 +591,25 @@ impl State {
         let spacing = if state.display_bytes {
             Spacing {
                 show_separator: show_prefix
-                    && (show_current_total || show_percentage || show_eta || show_message),
-                show_space_before_eta: show_percentage || show_current_total,
-                show_space_before_message: show_percentage || show_current_total || show_eta,
+                    && (show_current_total
+                        || show_percentage
+                        || show_rate
+                        || show_eta
+                        || show_message),
+                show_space_before_rate: show_percentage || show_current_total,
+                show_space_before_eta: show_percentage || show_current_total || show_rate,
+                show_space_before_message: show_percentage
+                    || show_current_total
+                    || show_rate
+                    || show_eta,
             }
         } else {
             Spacing {
-                show_separator: show_prefix && (show_percentage || show_eta || show_message),
-                show_space_before_eta: show_percentage,
-                show_space_before_message: show_percentage || show_eta,
+                show_separator: show_prefix
+                    && (show_percentage || show_rate || show_eta || show_message),
+                show_space_before_rate: show_percentage,
+                show_space_before_eta: show_percentage || show_rate,
+                show_space_before_message: show_percentage || show_rate || show_eta,
             }
         };
 
@@ -616,6 +697,20 @@ impl State {
             }
         }
 
+        if let Some(rate) = rate {
+            if spacing.show_space_before_rate && width > 0 {
+                out.push(' ');
+                width -= 1;
+            }
+            temp.clear();
+            format_rate(temp, rate, state.display_bytes);
+            temp.push(';');
+            if width >= temp.len() {
+                width -= temp.len();
+                out.push_str(temp);
+            }
+        }
+
         if let Some(eta) = eta {
             // ETA SS.SSs
             if spacing.show_space_before_eta && width > 0 {
@@ -640,18 +735,47 @@ impl State {
         }
     }
 
+    fn format_template(
+        &mut self,
+        template: &ProgressTemplate,
+        fmt: &mut BarFormatter<'_, '_, '_>,
+        width: usize,
+        state: &StateImmut,
+    ) {
+        let (current, total) = self.real_current_total(state.unbounded);
+        let eta = self.estimate_remaining(state.unbounded, fmt.now, fmt.tick);
+        let rate = self.estimate_rate(state.unbounded, fmt.tick);
+        let values = TemplateValues {
+            prefix: &state.prefix,
+            message: &self.message,
+            current,
+            total,
+            unbounded: state.unbounded,
+            in_bytes: state.display_bytes,
+            eta,
+            elapsed: self.start.elapsed().as_secs_f32(),
+            rate,
+            tick: fmt.tick,
+        };
+        // space after the spinner
+        if width > 0 {
+            fmt.out.push(' ');
+        }
+        template.format(fmt.out, width.saturating_sub(1), &values);
+    }
+
     fn format_finish_message(&self, message: &str, unbounded: bool, in_bytes: bool) -> String {
         if unbounded {
             return message.to_string();
         }
         let (current, total) = self.real_current_total(unbounded);
-        match (total, in_bytes) {
+        let mut out = match (total, in_bytes) {
             (None, false) => {
                 format!("[{current}/?] {message}")
             }
             (None, true) => {
                 let current = cu::ByteFormat(current);
-                format!("{message} ({current})")
+                format!("{message} ({current}")
             }
             (Some(total), false) => {
                 format!("[{current}/{total}] {message}")
@@ -659,12 +783,39 @@ impl State {
             (Some(total), true) => {
                 let current = cu::ByteFormat(current);
                 let total = cu::ByteFormat(total);
-                format!("{message} ({current} / {total})")
+                format!("{message} ({current} / {total}")
+            }
+        };
+        // show the average rate over the whole progress
+        match (&self.rate, in_bytes) {
+            (None, false) => {}
+            (None, true) => out.push(')'),
+            (Some(rate), false) => {
+                out.push_str(" (");
+                format_rate(&mut out, rate.average(current), false);
+                out.push(')');
+            }
+            (Some(rate), true) => {
+                out.push_str(", ");
+                format_rate(&mut out, rate.average(current), true);
+                out.push(')');
             }
         }
+        out
     }
 }
 
+/// Format the rate as steps or bytes per second
+pub(crate) fn format_rate(out: &mut String, rate: f32, in_bytes: bool) {
+    use std::fmt::Write as _;
+    // _: fmt for string does not fail
+    let _ = if in_bytes {
+        write!(out, "{}/s", cu::ByteFormat(rate as u64))
+    } else {
+        write!(out, "{rate:.1}/s")
+    };
+}
+
 fn format_message_with_width(out: &mut String, mut width: usize, message: &str) -> usize {
     for (c, w) in ansi::with_width(message.chars()) {
         if w > width {
@@ -0,0 +1,484 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use crate::cli::Tick;
+use crate::cli::fmt::{ansi, glyphs};
+
+/// Max width of a `{bar}` without a width, so it doesn't get too long
+/// in wide terminals or when the width is not limited
+const MAX_FLEXIBLE_BAR_WIDTH: usize = 100;
+
+/// Template for the layout of a progress bar
+///
+/// The template is displayed after the spinner and a space, and
+/// is a string with placeholders in `{}`. Use `{{` and `}}`
+/// for literal braces. The supported placeholders are:
+/// - `{steps}`: The current and total steps, for example `42/100`, or `42/?`
+///   if the total is not known
+/// - `{bytes}`: The current and total steps in bytes, for example `10.0k / 97.3M`
+/// - `{percent}`: The percentage, for example `42.00%`
+/// - `{eta}`: Estimated remaining time, for example `32.35s`
+/// - `{elapsed}`: Time since the bar is spawned, for example `5.10s`
+/// - `{rate}`: The throughput, for example `12.5/s`, or `1.2M/s` if the bar
+///   is in bytes
+/// - `{prefix}`: The message the bar is spawned with
+/// - `{message}`: The message set while the bar is running
+/// - `{bar}`: A graphical bar, for example `████░░░░`
+///
+/// The step values are empty if the bar is unbounded, and `{eta}`/`{rate}`
+/// are empty until there is enough data to estimate.
+///
+/// A width can be specified with `{name:WIDTH}`, which formats the value
+/// in exactly that many columns, by padding (numbers are aligned to the right)
+/// or truncating. `{bar}` without a width takes all the space left on the line,
+/// up to 100 columns.
+///
+/// When the line is longer than the terminal width, values with a lower priority
+/// are removed first (`{prefix}` and `{message}` are truncated instead of removed).
+/// The default priority, from lowest to highest, is
+/// `message`, `prefix`, `bar`, `elapsed`, `rate`, `eta`, `bytes`, `percent`, `steps`.
+/// The priority can be changed with `{name:WIDTH:PRIORITY}` or `{name::PRIORITY}`,
+/// where the default priorities are `1` to `9` in the order above.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// # fn main() -> cu::Result<()> {
+/// let template = cu::cli::ProgressTemplate::new("{prefix} {bar:20} {percent:7} {message}")?;
+/// let bar = cu::progress("building").total(10).template(template).spawn();
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgressTemplate {
+    parts: Arc<[Part]>,
+}
+
+#[derive(Debug)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug)]
+struct Placeholder {
+    component: Component,
+    width: Option<usize>,
+    priority: u32,
+}
+
+/// Component in the template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component {
+    Message,
+    Prefix,
+    Bar,
+    Elapsed,
+    Rate,
+    Eta,
+    Bytes,
+    Percent,
+    Steps,
+}
+
+impl Component {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "message" => Self::Message,
+            "prefix" => Self::Prefix,
+            "bar" => Self::Bar,
+            "elapsed" => Self::Elapsed,
+            "rate" => Self::Rate,
+            "eta" => Self::Eta,
+            "bytes" => Self::Bytes,
+            "percent" => Self::Percent,
+            "steps" => Self::Steps,
+            _ => return None,
+        })
+    }
+    /// Default priority, in the order of declaration
+    fn default_priority(self) -> u32 {
+        self as u32 + 1
+    }
+    /// If the value is truncated instead of removed
+    fn is_text(self) -> bool {
+        matches!(self, Self::Message | Self::Prefix)
+    }
+}
+
+impl ProgressTemplate {
+    /// Parse the template. See [`ProgressTemplate`] for the syntax
+    pub fn new(template: &str) -> crate::Result<Self> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    if chars.as_str().starts_with('{') {
+                        chars.next();
+                        literal.push('{');
+                        continue;
+                    }
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        crate::bail!("unclosed `{{` in progress template: {template}");
+                    };
+                    let placeholder = Placeholder::parse(&rest[..end])?;
+                    chars = rest[end + 1..].chars();
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Placeholder(placeholder));
+                }
+                '}' => {
+                    if !chars.as_str().starts_with('}') {
+                        crate::bail!("unmatched `}}` in progress template: {template}");
+                    }
+                    chars.next();
+                    literal.push('}');
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self {
+            parts: parts.into(),
+        })
+    }
+
+    /// Check if the template uses the component
+    pub(crate) fn uses(&self, component: Component) -> bool {
+        self.parts
+            .iter()
+            .any(|x| matches!(x, Part::Placeholder(p) if p.component == component))
+    }
+
+    /// Format the template with the values, in at most `width` columns
+    pub(crate) fn format(&self, out: &mut String, width: usize, values: &TemplateValues<'_>) {
+        // format the values that do not depend on the layout first
+        let mut rendered = Vec::with_capacity(self.parts.len());
+        let mut total_width = 0;
+        for part in self.parts.iter() {
+            let (text, w) = match part {
+                Part::Literal(x) => (None, ansi::width(x)),
+                Part::Placeholder(p) => {
+                    let mut text = String::new();
+                    let w = p.render(&mut text, values);
+                    (Some(text), w)
+                }
+            };
+            total_width += w;
+            rendered.push((text, w));
+        }
+
+        // remove or truncate values with the lowest priority,
+        // until the line fits
+        if total_width > width {
+            let mut order = self
+                .parts
+                .iter()
+                .enumerate()
+                .filter_map(|(i, x)| match x {
+                    Part::Placeholder(p) => Some((i, p)),
+                    Part::Literal(_) => None,
+                })
+                .collect::<Vec<_>>();
+            // later ones are removed first if same priority
+            order.sort_by_key(|(i, p)| (p.priority, std::cmp::Reverse(*i)));
+            for (i, p) in order {
+                if total_width <= width {
+                    break;
+                }
+                let w = &mut rendered[i].1;
+                let new_w = if p.component.is_text() {
+                    w.saturating_sub(total_width - width)
+                } else {
+                    0
+                };
+                total_width -= *w - new_w;
+                *w = new_w;
+            }
+        }
+
+        // distribute the remaining space to the bars without width
+        let flexible = self
+            .parts
+            .iter()
+            .filter(|x| matches!(x, Part::Placeholder(p) if p.is_flexible()))
+            .count();
+        let mut space = width.saturating_sub(total_width);
+        let mut flexible_left = flexible;
+
+        let mut remaining = width;
+        for (part, (text, w)) in self.parts.iter().zip(rendered) {
+            match part {
+                Part::Literal(x) => {
+                    remaining = push_with_width(out, remaining, x);
+                }
+                Part::Placeholder(p) if p.is_flexible() => {
+                    let bar_width = space / flexible_left;
+                    space -= bar_width;
+                    flexible_left -= 1;
+                    let bar_width = bar_width.min(remaining).min(MAX_FLEXIBLE_BAR_WIDTH);
+                    push_bar(out, bar_width, values);
+                    remaining -= bar_width;
+                }
+                Part::Placeholder(p) if p.component == Component::Bar => {
+                    // width is either the specified width, or 0 if removed
+                    let bar_width = w.min(remaining);
+                    push_bar(out, bar_width, values);
+                    remaining -= bar_width;
+                }
+                Part::Placeholder(_) => {
+                    // unwrap: placeholders are always rendered
+                    let text = text.unwrap();
+                    let w = w.min(remaining);
+                    let left = push_with_width(out, w, &text);
+                    remaining -= w - left;
+                }
+            }
+        }
+    }
+}
+
+impl Placeholder {
+    fn parse(spec: &str) -> crate::Result<Self> {
+        let mut parts = spec.split(':');
+        // unwrap: split always has at least one part
+        let name = parts.next().unwrap().trim();
+        let Some(component) = Component::parse(name) else {
+            crate::bail!("unknown placeholder `{{{spec}}}` in progress template");
+        };
+        let width = match parts.next().map(str::trim) {
+            None | Some("") => None,
+            Some(x) => match x.parse::<usize>() {
+                Ok(x) => Some(x),
+                Err(_) => crate::bail!("invalid width in placeholder `{{{spec}}}`"),
+            },
+        };
+        let priority = match parts.next().map(str::trim) {
+            None | Some("") => component.default_priority(),
+            Some(x) => match x.parse::<u32>() {
+                Ok(x) => x,
+                Err(_) => crate::bail!("invalid priority in placeholder `{{{spec}}}`"),
+            },
+        };
+        if parts.next().is_some() {
+            crate::bail!("too many `:` in placeholder `{{{spec}}}`");
+        }
+        Ok(Self {
+            component,
+            width,
+            priority,
+        })
+    }
+
+    fn is_flexible(&self) -> bool {
+        self.component == Component::Bar && self.width.is_none()
+    }
+
+    /// Render the value to `out` and return the width
+    fn render(&self, out: &mut String, values: &TemplateValues<'_>) -> usize {
+        if self.component == Component::Bar {
+            // the bar is pushed when the width is known
+            return self.width.unwrap_or_default();
+        }
+        // _: fmt for string does not fail
+        let _ = match self.component {
+            Component::Message => write!(out, "{}", values.message),
+            Component::Prefix => write!(out, "{}", values.prefix),
+            Component::Steps if values.unbounded => Ok(()),
+            Component::Steps => match values.total {
+                None => write!(out, "{}/?", values.current),
+                Some(total) => write!(out, "{}/{total}", values.current),
+            },
+            Component::Bytes if values.unbounded => Ok(()),
+            Component::Bytes => match values.total {
+                None => write!(out, "{}", cu::ByteFormat(values.current)),
+                Some(total) => write!(
+                    out,
+                    "{} / {}",
+                    cu::ByteFormat(values.current),
+                    cu::ByteFormat(total)
+                ),
+            },
+            Component::Percent => match values.total {
+                None => Ok(()),
+                Some(total) if values.current == total => write!(out, "100%"),
+                Some(total) => {
+                    let percentage = values.current as f32 * 100f32 / total as f32;
+                    write!(out, "{percentage:.2}%")
+                }
+            },
+            Component::Eta => match values.eta {
+                None => Ok(()),
+                Some(eta) => write!(out, "{eta:.2}s"),
+            },
+            Component::Elapsed => write!(out, "{:.2}s", values.elapsed),
+            Component::Rate => {
+                if let Some(rate) = values.rate {
+                    super::state::format_rate(out, rate, values.in_bytes);
+                }
+                Ok(())
+            }
+            Component::Bar => Ok(()),
+        };
+        let mut w = ansi::width(out);
+        if let Some(width) = self.width {
+            if w < width {
+                if self.component.is_text() {
+                    out.extend(std::iter::repeat_n(' ', width - w));
+                } else {
+                    out.insert_str(0, &" ".repeat(width - w));
+                }
+            }
+            w = width;
+        }
+        w
+    }
+}
+
+/// Values of the bar to format the template
+pub(crate) struct TemplateValues<'a> {
+    pub prefix: &'a str,
+    pub message: &'a str,
+    pub current: u64,
+    pub total: Option<u64>,
+    pub unbounded: bool,
+    pub in_bytes: bool,
+    pub eta: Option<f32>,
+    pub elapsed: f32,
+    pub rate: Option<f32>,
+    pub tick: Tick,
+}
+
+/// Push the graphical bar in `width` columns
+fn push_bar(out: &mut String, width: usize, values: &TemplateValues<'_>) {
+    let glyphs = glyphs();
+    let total = match values.total {
+        Some(total) if !values.unbounded => total,
+        _ => {
+            // progress not known, animate a segment moving across the bar
+            if width == 0 {
+                return;
+            }
+            let len = width.min(3);
+            let start = (values.tick as usize / 5) % width;
+            for i in 0..width {
+                let filled = (i + width - start) % width < len;
+                out.push(if filled {
+                    glyphs.bar_filled
+                } else {
+                    glyphs.bar_empty
+                });
+            }
+            return;
+        }
+    };
+    let filled = (values.current.min(total) as u128 * width as u128 / total as u128) as usize;
+    out.extend(std::iter::repeat_n(glyphs.bar_filled, filled));
+    out.extend(std::iter::repeat_n(glyphs.bar_empty, width - filled));
+}
+
+/// Push the text truncated to `width`, return the width left
+fn push_with_width(out: &mut String, mut width: usize, text: &str) -> usize {
+    for (c, w) in ansi::with_width(text.chars()) {
+        if w > width {
+            break;
+        }
+        width -= w;
+        out.push(c);
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> TemplateValues<'static> {
+        TemplateValues {
+            prefix: "building",
+            message: "compiling foo",
+            current: 42,
+            total: Some(100),
+            unbounded: false,
+            in_bytes: false,
+            eta: Some(1.5),
+            elapsed: 2.0,
+            rate: None,
+            tick: 0,
+        }
+    }
+
+    fn format(template: &str, width: usize) -> String {
+        let template = ProgressTemplate::new(template).unwrap();
+        let mut out = String::new();
+        template.format(&mut out, width, &values());
+        out
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(ProgressTemplate::new("{unknown}").is_err());
+        assert!(ProgressTemplate::new("{steps").is_err());
+        assert!(ProgressTemplate::new("steps}").is_err());
+        assert!(ProgressTemplate::new("{steps:x}").is_err());
+        assert!(ProgressTemplate::new("{{steps}}").is_ok());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            format("[{steps}] {prefix}: {percent} ETA {eta} {message}", 100),
+            "[42/100] building: 42.00% ETA 1.50s compiling foo"
+        );
+        assert_eq!(format("{{{steps:8}}}", 100), "{  42/100}");
+        assert_eq!(
+            format("{prefix:4}|{message:15}|", 100),
+            "buil|compiling foo  |"
+        );
+    }
+
+    #[test]
+    fn test_format_bar() {
+        let glyphs = glyphs();
+        let bar = |filled, empty| {
+            let mut out = String::new();
+            out.extend(std::iter::repeat_n(glyphs.bar_filled, filled));
+            out.extend(std::iter::repeat_n(glyphs.bar_empty, empty));
+            out
+        };
+        assert_eq!(format("[{bar:10}]", 100), format!("[{}]", bar(4, 6)));
+        assert_eq!(
+            format("[{bar}] {steps}", 20),
+            format!("[{}] 42/100", bar(4, 7))
+        );
+    }
+
+    #[test]
+    fn test_format_huge_width() {
+        let glyphs = glyphs();
+        let out = format("[{bar}] {steps} {message}", usize::MAX / 2);
+        let filled = out.chars().filter(|c| *c == glyphs.bar_filled).count();
+        let empty = out.chars().filter(|c| *c == glyphs.bar_empty).count();
+        assert_eq!(filled + empty, MAX_FLEXIBLE_BAR_WIDTH);
+        assert!(out.ends_with("] 42/100 compiling foo"));
+        assert_eq!(
+            format("{bar:10}|{message}|", usize::MAX),
+            format("{bar:10}|{message}|", 100)
+        );
+    }
+
+    #[test]
+    fn test_format_priority() {
+        let template = "{steps} {prefix}: {percent} {message}";
+        assert_eq!(format(template, 30), "42/100 building: 42.00% compil");
+        assert_eq!(format(template, 20), "42/100 buil: 42.00% ");
+        assert_eq!(
+            format("{steps} {prefix}: {percent::0} {message}", 20),
+            "42/100 building:  co"
+        );
+    }
+}
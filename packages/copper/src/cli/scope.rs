@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use crate::lv;
+
+thread_local! {
+    /// Names of the scopes currently entered by this thread, outermost first
+    pub(crate) static SCOPES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Get how many scopes the current thread is in
+#[inline(always)]
+pub(crate) fn depth() -> usize {
+    SCOPES.with_borrow(|x| x.len())
+}
+
+/// # Scopes
+/// A scope groups the messages printed by the current thread under a header.
+/// While the scope is active, messages from the same thread are indented,
+/// and scopes can be nested.
+///
+/// The header is printed at `info` level when the scope starts. When the scope
+/// ends, the elapsed time is printed at the same indentation as the header.
+///
+/// Similar to [progress bars](fn@crate::progress), you need to call [`done`](Scope::done)
+/// to mark the scope as successful. If the scope is dropped without calling `done`
+/// (for example, when returning early with `?`, or when panicking),
+/// it will be printed as failed at `error` level.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// fn build() -> cu::Result<()> {
+///     let scope = cu::scope!("building frontend");
+///     cu::info!("bundling");
+///     {
+///         let scope = cu::scope!("compressing assets");
+///         cu::info!("compressed 42 files");
+///         scope.done();
+///     }
+///     scope.done();
+///     Ok(())
+/// }
+/// # cu::cli::level("");
+/// # build().unwrap();
+/// ```
+/// This prints:
+/// ```text
+/// I] building frontend
+/// I]   bundling
+/// I]   compressing assets
+/// I]     compressed 42 files
+/// I]   compressing assets: done in 0.00s
+/// I] building frontend: done in 0.00s
+/// ```
+/// The time is not printed if disabled with
+/// [`cu::lv::disable_print_time`](crate::lv::disable_print_time).
+///
+/// Since the scope is for the current thread, the [`Scope`] guard cannot be sent
+/// to another thread. Use [`cu::cli::set_thread_name`](crate::cli::set_thread_name)
+/// to identify messages from other threads.
+#[track_caller]
+pub fn scope(name: impl Into<String>) -> Scope {
+    let name = name.into();
+    crate::cli::__print_with_level(lv::I, format_args!("{name}"));
+    let depth = SCOPES.with_borrow_mut(|x| {
+        let depth = x.len();
+        x.push(name.clone());
+        depth
+    });
+    Scope {
+        name,
+        depth,
+        start: Instant::now(),
+        is_done: false,
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard for a [scope](fn@crate::cli::scope). The scope ends when dropped
+#[must_use = "the scope ends immediately if the guard is not kept"]
+pub struct Scope {
+    name: String,
+    depth: usize,
+    start: Instant,
+    is_done: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Scope {
+    /// End the scope successfully
+    #[inline(always)]
+    pub fn done(mut self) {
+        self.is_done = true;
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        // also remove inner scopes in case they are leaked
+        SCOPES.with_borrow_mut(|x| x.truncate(self.depth));
+        let name = &self.name;
+        let is_done = self.is_done && !std::thread::panicking();
+        if !lv::is_print_time_enabled() {
+            if is_done {
+                crate::cli::__print_with_level(lv::I, format_args!("{name}: done"));
+            } else {
+                crate::cli::__print_with_level(lv::E, format_args!("{name}: failed"));
+            }
+            return;
+        }
+        let elapsed = self.start.elapsed().as_secs_f32();
+        if is_done {
+            crate::cli::__print_with_level(lv::I, format_args!("{name}: done in {elapsed:.2}s"));
+        } else {
+            crate::cli::__print_with_level(
+                lv::E,
+                format_args!("{name}: failed after {elapsed:.2}s"),
+            );
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "cli")]
+use crate::cli::printer::PRINTER;
+use crate::lv;
+
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Messages to print again at exit, if recap is enabled
+static RECAP: Mutex<Vec<(lv::Lv, String)>> = Mutex::new(Vec::new());
+
+/// Get the number of warnings printed so far
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Acquire)
+}
+
+/// Get the number of errors printed so far
+pub fn error_count() -> usize {
+    ERROR_COUNT.load(Ordering::Acquire)
+}
+
+/// Count the message if it's a warning or error
+pub(crate) fn record(lv: lv::Lv, message: &str) {
+    let counter = match lv {
+        lv::Lv::Warn => &WARNING_COUNT,
+        lv::Lv::Error => &ERROR_COUNT,
+        _ => return,
+    };
+    counter.fetch_add(1, Ordering::AcqRel);
+    if lv::is_recap_enabled() {
+        let mut recap = RECAP.lock().unwrap_or_else(|e| e.into_inner());
+        recap.push((lv, message.to_string()));
+    }
+}
+
+/// Format the counts like `4 warnings, 1 error`. Empty if there are no warnings or errors
+#[cfg(feature = "cli")]
+pub(crate) fn format_counts() -> String {
+    let counts = [
+        (warning_count(), "warning", "warnings"),
+        (error_count(), "error", "errors"),
+    ];
+    let mut out = String::new();
+    for (count, singular, plural) in counts {
+        if count == 0 {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(", ");
+        }
+        let noun = if count == 1 { singular } else { plural };
+        out.push_str(&format!("{count} {noun}"));
+    }
+    out
+}
+
+/// Print the recorded warnings and errors again, if recap is enabled
+#[cfg(feature = "cli")]
+pub(crate) fn print_recap() {
+    let recap = std::mem::take(&mut *RECAP.lock().unwrap_or_else(|e| e.into_inner()));
+    if recap.is_empty() {
+        return;
+    }
+    let Ok(mut printer) = PRINTER.lock() else {
+        return;
+    };
+    let Some(printer) = printer.as_mut() else {
+        return;
+    };
+    let header = format!("recap: {}", format_counts());
+    printer.print_recap(&header, &recap);
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recap() {
+        let was_enabled = lv::is_recap_enabled();
+        lv::enable_recap();
+        let capture = crate::cli::capture();
+        crate::warn!("disk is almost full");
+        print_recap();
+        let messages = capture.messages();
+        assert!(
+            messages
+                .iter()
+                .any(|(lv, message)| *lv == lv::P && message.starts_with("recap: ")),
+            "{messages:?}"
+        );
+        let warning = (lv::W, "disk is almost full".to_string());
+        assert_eq!(messages.iter().filter(|x| **x == warning).count(), 2);
+        assert!(!format_counts().is_empty());
+        lv::ENABLE_RECAP.store(was_enabled, Ordering::Release);
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt::Display;
+
+use crate::cli::Block;
+use crate::cli::fmt::{self, JsonRecord, ansi};
+use crate::lv;
+
+/// Separator between columns
+const COLUMN_GAP: &str = "  ";
+/// Columns are not truncated to less than this width
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// # Tables
+/// Print rows of data in aligned columns.
+///
+/// The table is printed through the same printer as the messages, so
+/// it will not interfere with progress bars that are active. Widths of wide characters
+/// (for example, CJK characters) and [styled text](mod@crate::style) are handled correctly.
+///
+/// If the table is wider than the terminal, the widest columns are truncated
+/// to make it fit, and truncated cells end with `…`.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::cli::{Align, Table};
+///
+/// let mut table = Table::new(["Name", "Version", "Size"]).align(2, Align::Right);
+/// table.row(["serde", "1.0.228", "78K"]);
+/// table.row(["unicode-width", "0.2.2", "1.2M"]);
+/// table.print();
+/// ```
+/// This prints:
+/// ```text
+/// Name           Version  Size
+/// ─────────────  ───────  ────
+/// serde          1.0.228   78K
+/// unicode-width  0.2.2    1.2M
+/// ```
+///
+/// The table is printed at the same level as [`cu::print!`](macro@crate::print).
+/// With `--log-format json`, the table is printed as one record with the
+/// `columns` and `rows`.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    aligns: Vec<Align>,
+    rows: Vec<Vec<String>>,
+    max_width: Option<usize>,
+}
+
+/// Alignment of a column in a [`Table`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+impl Table {
+    /// Create a table with the column headers. If all the headers are empty,
+    /// the header row is not printed
+    pub fn new<I>(headers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let headers: Vec<_> = headers.into_iter().map(|x| x.to_string()).collect();
+        let aligns = vec![Align::Left; headers.len()];
+        Self {
+            headers,
+            aligns,
+            rows: vec![],
+            max_width: None,
+        }
+    }
+
+    /// Set the alignment of the i-th column (starting at 0). Columns are left-aligned by default
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        if let Some(x) = self.aligns.get_mut(column) {
+            *x = align;
+        }
+        self
+    }
+
+    /// Set the max width of the table. By default, the table is truncated to the terminal width
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Add a row. Extra cells are ignored, and missing cells are empty
+    pub fn row<I>(&mut self, cells: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let mut row: Vec<_> = cells
+            .into_iter()
+            .take(self.headers.len())
+            .map(|x| x.to_string().replace(['\r', '\n'], " "))
+            .collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+        self
+    }
+
+    /// Print the table
+    pub fn print(&self) {
+        crate::cli::print_block(lv::P, self)
+    }
+
+    /// Compute the width of each column, truncated to fit in `max_width`
+    fn column_widths(&self, max_width: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<_> = self.headers.iter().map(|x| ansi::width(x)).collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(ansi::width(cell));
+            }
+        }
+        let Some(max_width) = max_width else {
+            return widths;
+        };
+        let gaps = COLUMN_GAP.len() * widths.len().saturating_sub(1);
+        let mut total: usize = widths.iter().sum::<usize>() + gaps;
+        while total > max_width {
+            // shrink the widest column
+            let Some(widest) = widths.iter_mut().max() else {
+                break;
+            };
+            if *widest <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            *widest -= 1;
+            total -= 1;
+        }
+        widths
+    }
+}
+
+impl Block for Table {
+    fn render(&self, out: &mut String, max_width: Option<usize>, colors: &ansi::Colors) {
+        let max_width = self.max_width.or(max_width);
+        let widths = self.column_widths(max_width);
+        if self.headers.iter().any(|x| !x.is_empty()) {
+            let is_colored = !colors.reset.is_empty();
+            if is_colored {
+                out.push_str("\x1b[1m");
+            }
+            push_row(out, &self.headers, &widths, &self.aligns);
+            if is_colored {
+                out.push_str("\x1b[22m");
+            }
+            out.push('\n');
+            out.push_str(colors.decoration);
+            for (i, width) in widths.iter().enumerate() {
+                if i != 0 {
+                    out.push_str(COLUMN_GAP);
+                }
+                for _ in 0..*width {
+                    out.push(fmt::glyphs().line);
+                }
+            }
+            out.push_str(colors.reset);
+            out.push('\n');
+        }
+        for row in &self.rows {
+            push_row(out, row, &widths, &self.aligns);
+            out.push('\n');
+        }
+    }
+
+    fn json_record(&self, out: &mut String) {
+        let mut array = String::from("[");
+        push_json_array(&mut array, &self.headers);
+        array.push(']');
+        let record = JsonRecord::new(out, "table").raw("columns", &array);
+        array.clear();
+        array.push('[');
+        for (i, row) in self.rows.iter().enumerate() {
+            if i != 0 {
+                array.push(',');
+            }
+            array.push('[');
+            push_json_array(&mut array, row);
+            array.push(']');
+        }
+        array.push(']');
+        record.raw("rows", &array).finish();
+    }
+}
+
+fn push_row(out: &mut String, cells: &[String], widths: &[usize], aligns: &[Align]) {
+    // don't leave trailing spaces at the end
+    let last_non_empty = cells.iter().rposition(|x| !x.is_empty()).unwrap_or(0);
+    for (i, ((cell, width), align)) in cells.iter().zip(widths).zip(aligns).enumerate() {
+        if i > last_non_empty {
+            break;
+        }
+        if i != 0 {
+            out.push_str(COLUMN_GAP);
+        }
+        let cell_width = ansi::width(cell);
+        let padding = width.saturating_sub(cell_width);
+        let (left, right) = match align {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+        push_spaces(out, left);
+        let remaining = ansi::push_truncated(out, cell, *width);
+        if i != last_non_empty {
+            push_spaces(out, right + remaining);
+        }
+    }
+}
+
+fn push_spaces(out: &mut String, n: usize) {
+    for _ in 0..n {
+        out.push(' ');
+    }
+}
+
+fn push_json_array(out: &mut String, x: &[String]) {
+    for (i, x) in x.iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        fmt::push_json_str(out, x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Theme;
+
+    #[test]
+    fn test_render() {
+        let mut table = Table::new(["a", "b", "c"])
+            .align(1, Align::Right)
+            .align(2, Align::Center);
+        table.row(["\u{4f60}\u{597d}", "1", "x"]);
+        table.row(["long cell", "12345", ""]);
+        let colors = ansi::Colors::new(&Theme::default(), None);
+        let mut out = String::new();
+        table.render(&mut out, None, &colors);
+        assert_eq!(
+            out,
+            "\
+a              b  c
+─────────  ─────  ─
+你好           1  x
+long cell  12345
+"
+        );
+        let mut out = String::new();
+        table.render(&mut out, Some(15), &colors);
+        assert_eq!(
+            out,
+            "\
+a          b  c
+─────  ─────  ─
+你好       1  x
+long…  12345
+"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::sync::OnceLock;
+
+/// # Themes
+/// The theme controls the colors and prefix symbols used when printing
+/// messages, prompts, and progress bars.
+///
+/// To use a custom theme, return it from [`LogConfig::theme`](crate::cli::LogConfig::theme).
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// use cu::cli::{Theme, ThemeColor};
+///
+/// struct LogConfig;
+/// impl cu::cli::LogConfig for LogConfig {
+///     fn process(&self, record: &cu::lv::LogRecord) -> (cu::lv::Lv, bool) {
+///         cu::cli::DefaultLogConfig.process(record)
+///     }
+///     fn theme(&self) -> Theme {
+///         let mut theme = Theme::light();
+///         theme.info.symbol = "i";
+///         theme.progress = ThemeColor::Rgb(0, 135, 255);
+///         theme
+///     }
+/// }
+///
+/// #[cu::cli(log_config = |_| LogConfig)]
+/// fn main(_: cu::cli::Flags) -> cu::Result<()> {
+///     Ok(())
+/// }
+/// ```
+///
+/// Colors are automatically downgraded if the terminal does not support them:
+/// - If `COLORTERM` is `truecolor` or `24bit`, all colors are supported.
+/// - Otherwise, if `TERM` contains `256color`, [`ThemeColor::Rgb`] is approximated
+///   with the 256-color palette
+/// - Otherwise, only the 16 basic colors are used
+///
+/// The theme has no effect if color is disabled, except for the symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Theme {
+    /// Style for `error` messages
+    pub error: LevelStyle,
+    /// Style for `hint` messages
+    pub hint: LevelStyle,
+    /// Style for `print` messages
+    pub print: LevelStyle,
+    /// Style for `warn` messages
+    pub warn: LevelStyle,
+    /// Style for `info` messages
+    pub info: LevelStyle,
+    /// Style for `debug` messages
+    pub debug: LevelStyle,
+    /// Style for `trace` messages
+    pub trace: LevelStyle,
+    /// Style for prompts
+    pub prompt: LevelStyle,
+    /// Color for decorations, such as the `|` before continuation lines
+    pub decoration: ThemeColor,
+    /// Color for the thread name
+    pub thread_name: ThemeColor,
+    /// Color for progress bars that are in progress
+    pub progress: ThemeColor,
+    /// Color for progress bars that are done
+    pub progress_done: ThemeColor,
+    /// Color for progress bars that are interrupted
+    pub progress_interrupted: ThemeColor,
+    /// Color for the glyphs connecting the nodes in a tree, such as the progress bar hierarchy
+    pub tree: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        use ThemeColor::*;
+        Self {
+            error: LevelStyle::new("E", Ansi(9), "]", Ansi(9), Ansi(9)),
+            hint: LevelStyle::new("H", Ansi(14), "]", Ansi(8), Ansi(11)),
+            print: LevelStyle::new(":", Ansi(8), ":", Ansi(8), Default),
+            warn: LevelStyle::new("W", Ansi(11), "]", Ansi(11), Ansi(11)),
+            info: LevelStyle::new("I", Ansi(10), "]", Ansi(8), Default),
+            debug: LevelStyle::new("D", Ansi(8), "]", Ansi(8), Ansi(14)),
+            trace: LevelStyle::new("*", Ansi(13), "]", Ansi(13), Ansi(13)),
+            prompt: LevelStyle::new("!", Ansi(14), "]", Ansi(14), Ansi(14)),
+            decoration: Ansi(8),
+            thread_name: Ansi(13),
+            progress: Ansi(11),
+            progress_done: Ansi(10),
+            progress_interrupted: Ansi(11),
+            tree: Ansi(8),
+        }
+    }
+}
+
+impl Theme {
+    /// Theme for terminals with a light background.
+    ///
+    /// This uses the normal colors instead of the bright ones, and uses
+    /// the default text color instead of gray for decorations.
+    pub fn light() -> Self {
+        use ThemeColor::*;
+        Self {
+            error: LevelStyle::new("E", Ansi(1), "]", Ansi(1), Ansi(1)),
+            hint: LevelStyle::new("H", Ansi(6), "]", Default, Ansi(3)),
+            print: LevelStyle::new(":", Default, ":", Default, Default),
+            warn: LevelStyle::new("W", Ansi(3), "]", Ansi(3), Ansi(3)),
+            info: LevelStyle::new("I", Ansi(2), "]", Default, Default),
+            debug: LevelStyle::new("D", Default, "]", Default, Ansi(6)),
+            trace: LevelStyle::new("*", Ansi(5), "]", Ansi(5), Ansi(5)),
+            prompt: LevelStyle::new("!", Ansi(6), "]", Ansi(6), Ansi(6)),
+            decoration: Default,
+            thread_name: Ansi(5),
+            progress: Ansi(3),
+            progress_done: Ansi(2),
+            progress_interrupted: Ansi(3),
+            tree: Default,
+        }
+    }
+}
+
+/// Style for the prefix and text of one kind of message. See [`Theme`]
+///
+/// The prefix is the symbol followed by the separator. For example, the
+/// prefix for `info` messages is `I]` by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelStyle {
+    /// First part of the prefix, usually one character
+    pub symbol: &'static str,
+    /// Color of the symbol
+    pub symbol_color: ThemeColor,
+    /// Second part of the prefix, usually one character
+    pub separator: &'static str,
+    /// Color of the separator
+    pub separator_color: ThemeColor,
+    /// Color of the message text
+    pub text_color: ThemeColor,
+}
+
+impl LevelStyle {
+    /// Create a style
+    pub const fn new(
+        symbol: &'static str,
+        symbol_color: ThemeColor,
+        separator: &'static str,
+        separator_color: ThemeColor,
+        text_color: ThemeColor,
+    ) -> Self {
+        Self {
+            symbol,
+            symbol_color,
+            separator,
+            separator_color,
+            text_color,
+        }
+    }
+}
+
+/// A color in the [`Theme`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeColor {
+    /// The default color of the terminal (resets the style)
+    Default,
+    /// One of the 16 basic colors. `0-7` are the normal colors,
+    /// and `8-15` are the bright colors
+    Ansi(u8),
+    /// One of the 256 colors in the extended palette
+    Fixed(u8),
+    /// 24-bit color
+    Rgb(u8, u8, u8),
+}
+
+/// Colors supported by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ColorSupport {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Get the color support of the terminal, detected once
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<ColorSupport> = OnceLock::new();
+        *CURRENT.get_or_init(Self::from_env)
+    }
+    /// Detect from `COLORTERM` and `TERM`
+    fn from_env() -> Self {
+        // not using cu::env_var, since we are before log initialization
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+        let term = std::env::var("TERM").unwrap_or_default();
+        if term.contains("256color") {
+            return Self::Ansi256;
+        }
+        Self::Ansi16
+    }
+}
+
+impl ThemeColor {
+    /// Get the escape code to set the foreground to this color,
+    /// downgraded to what the terminal supports
+    pub(crate) fn to_ansi(self, support: ColorSupport) -> String {
+        let color = match (self, support) {
+            (Self::Rgb(r, g, b), ColorSupport::Ansi256) => Self::Fixed(rgb_to_256(r, g, b)),
+            (Self::Rgb(r, g, b), ColorSupport::Ansi16) => Self::Ansi(rgb_to_16(r, g, b)),
+            (Self::Fixed(n), ColorSupport::Ansi16) => Self::Ansi(fixed_to_16(n)),
+            (color, _) => color,
+        };
+        match color {
+            Self::Default => "\x1b[0m".to_string(),
+            Self::Ansi(n) if n < 8 => format!("\x1b[{}m", 30 + n),
+            Self::Ansi(n) => format!("\x1b[{}m", 90 + (n.min(15) - 8)),
+            Self::Fixed(n) => format!("\x1b[38;5;{n}m"),
+            Self::Rgb(r, g, b) => format!("\x1b[38;2;{r};{g};{b}m"),
+        }
+    }
+}
+
+/// RGB values of the 16 basic colors, using xterm defaults
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of each channel in the 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |x: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - x as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or_default()
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+    let cube_rgb = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+    // the grayscale ramp may be closer
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (average.saturating_sub(8) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+    let gray_rgb = (gray_level, gray_level, gray_level);
+    if distance((r, g, b), gray_rgb) < distance((r, g, b), cube_rgb) {
+        232 + gray_index
+    } else {
+        cube
+    }
+}
+
+fn fixed_to_16(n: u8) -> u8 {
+    if n < 16 {
+        return n;
+    }
+    let (r, g, b) = if n >= 232 {
+        let level = 8 + 10 * (n - 232);
+        (level, level, level)
+    } else {
+        let n = n - 16;
+        (
+            CUBE_LEVELS[(n / 36) as usize],
+            CUBE_LEVELS[(n / 6 % 6) as usize],
+            CUBE_LEVELS[(n % 6) as usize],
+        )
+    };
+    rgb_to_16(r, g, b)
+}
+
+fn rgb_to_16(r: u8, g: u8, b: u8) -> u8 {
+    ANSI_16_RGB
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, rgb)| distance((r, g, b), **rgb))
+        .map(|(i, _)| i as u8)
+        .unwrap_or_default()
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downgrade() {
+        let orange = ThemeColor::Rgb(255, 135, 0);
+        assert_eq!(
+            orange.to_ansi(ColorSupport::TrueColor),
+            "\x1b[38;2;255;135;0m"
+        );
+        assert_eq!(orange.to_ansi(ColorSupport::Ansi256), "\x1b[38;5;208m");
+        assert_eq!(orange.to_ansi(ColorSupport::Ansi16), "\x1b[33m");
+        let gray = ThemeColor::Rgb(128, 128, 128);
+        assert_eq!(gray.to_ansi(ColorSupport::Ansi256), "\x1b[38;5;244m");
+        assert_eq!(
+            ThemeColor::Fixed(21).to_ansi(ColorSupport::Ansi16),
+            "\x1b[34m"
+        );
+        assert_eq!(
+            ThemeColor::Ansi(3).to_ansi(ColorSupport::Ansi16),
+            "\x1b[33m"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt::Display;
+
+use crate::cli::Block;
+use crate::cli::fmt::{self, JsonRecord, ansi};
+use crate::lv;
+
+/// # Trees
+/// Print hierarchical data, such as a dependency tree or a directory listing,
+/// using the same characters as the [progress bar](fn@crate::progress) hierarchy.
+///
+/// The tree is printed through the same printer as the messages, so
+/// it will not interfere with progress bars that are active. Labels can
+/// be [styled](mod@crate::style), and labels that are too long for the terminal
+/// are truncated with `…`.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::cli::Tree;
+///
+/// let mut tree = Tree::new("my-crate v0.1.0");
+/// let serde = tree.child("serde v1.0.228");
+/// serde.child("serde_derive v1.0.228");
+/// tree.child(cu::style::yellow("log v0.4.28"));
+/// let dev = tree.child("[dev-dependencies]").collapse();
+/// dev.child("criterion v0.7.0");
+/// dev.child("proptest v1.8.0");
+/// tree.print();
+/// ```
+/// This prints:
+/// ```text
+/// my-crate v0.1.0
+/// ├ serde v1.0.228
+/// │ └ serde_derive v1.0.228
+/// ├ log v0.4.28
+/// └ [dev-dependencies] (+2)
+/// ```
+///
+/// The tree is printed at the same level as [`cu::print!`](macro@crate::print).
+/// With `--log-format json`, the tree is printed as one record with nested
+/// `label` and `children`.
+#[derive(Debug, Clone, Default)]
+pub struct Tree {
+    label: String,
+    children: Vec<Tree>,
+    is_collapsed: bool,
+}
+
+impl Tree {
+    /// Create a tree with the root label
+    pub fn new(label: impl Display) -> Self {
+        Self {
+            label: label.to_string().replace(['\r', '\n'], " "),
+            children: vec![],
+            is_collapsed: false,
+        }
+    }
+
+    /// Add a child node with the label, and return the child
+    pub fn child(&mut self, label: impl Display) -> &mut Tree {
+        self.children.push(Tree::new(label));
+        let i = self.children.len() - 1;
+        &mut self.children[i]
+    }
+
+    /// Add a subtree as a child
+    pub fn add(&mut self, tree: Tree) -> &mut Self {
+        self.children.push(tree);
+        self
+    }
+
+    /// Hide the descendants of this node when printing. The number of hidden
+    /// nodes is displayed after the label instead
+    pub fn collapse(&mut self) -> &mut Self {
+        self.is_collapsed = true;
+        self
+    }
+
+    /// Print the tree
+    pub fn print(&self) {
+        crate::cli::print_block(lv::P, self)
+    }
+
+    /// Number of nodes under this node
+    fn descendant_count(&self) -> usize {
+        self.children.iter().map(|x| 1 + x.descendant_count()).sum()
+    }
+
+    fn render_children(
+        &self,
+        out: &mut String,
+        hierarchy: &mut String,
+        max_width: usize,
+        colors: &ansi::Colors,
+    ) {
+        let children_count = self.children.len();
+        for (i, child) in self.children.iter().enumerate() {
+            out.push_str(colors.tree);
+            out.push_str(hierarchy);
+            let is_last = i == children_count - 1;
+            let glyphs = fmt::glyphs();
+            out.push(if is_last {
+                glyphs.tick
+            } else {
+                glyphs.bar_tick
+            });
+            out.push(' ');
+            out.push_str(colors.reset);
+            // each level takes 2 columns
+            let width = max_width.saturating_sub(ansi::width(hierarchy) + 2);
+            child.render_label(out, width, colors);
+            if child.is_collapsed {
+                continue;
+            }
+            if is_last {
+                hierarchy.push_str("  ");
+            } else {
+                hierarchy.push(glyphs.bar);
+                hierarchy.push(' ');
+            }
+            child.render_children(out, hierarchy, max_width, colors);
+            hierarchy.pop();
+            hierarchy.pop();
+        }
+    }
+
+    fn render_label(&self, out: &mut String, max_width: usize, colors: &ansi::Colors) {
+        let hidden_count = if self.is_collapsed {
+            self.descendant_count()
+        } else {
+            0
+        };
+        if hidden_count == 0 {
+            ansi::push_truncated(out, &self.label, max_width);
+        } else {
+            let hidden = format!(" (+{hidden_count})");
+            let width = max_width.saturating_sub(hidden.len());
+            ansi::push_truncated(out, &self.label, width);
+            out.push_str(colors.decoration);
+            out.push_str(&hidden);
+            out.push_str(colors.reset);
+        }
+        out.push('\n');
+    }
+
+    fn json_node(&self, out: &mut String) {
+        out.push_str("{\"label\":");
+        fmt::push_json_str(out, &self.label);
+        out.push_str(",\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            child.json_node(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+impl Block for Tree {
+    fn render(&self, out: &mut String, max_width: Option<usize>, colors: &ansi::Colors) {
+        let max_width = max_width.unwrap_or(usize::MAX);
+        self.render_label(out, max_width, colors);
+        if !self.is_collapsed {
+            self.render_children(out, &mut String::new(), max_width, colors);
+        }
+    }
+
+    fn json_record(&self, out: &mut String) {
+        let mut root = String::new();
+        self.json_node(&mut root);
+        JsonRecord::new(out, "tree").raw("root", &root).finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Theme;
+
+    #[test]
+    fn test_render() {
+        let mut tree = Tree::new("root");
+        let a = tree.child("a");
+        a.child("a1").child("a11");
+        a.child("a2");
+        tree.child("b").collapse().child("b1").child("b11");
+        tree.child("c with a long label");
+        let colors = ansi::Colors::new(&Theme::default(), None);
+        let mut out = String::new();
+        tree.render(&mut out, Some(12), &colors);
+        assert_eq!(
+            out,
+            "\
+root
+├ a
+│ ├ a1
+│ │ └ a11
+│ └ a2
+├ b (+2)
+└ c with a …
+"
+        );
+    }
+}
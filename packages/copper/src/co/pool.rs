@@ -43,6 +43,9 @@ pub fn pool(capacity: isize) -> Pool {
 ///
 /// This is constructed with [`cu::co::pool`](function@pool),
 /// since it's shorter than `cu::co::Pool::new(n)`.
+///
+/// To show the progress of the spawned tasks, add the handles to a [`Set`]
+/// with a progress bar attached. See [Progress](Set#progress)
 #[derive(Clone)]
 pub struct Pool(Arc<PoolInner>);
 struct PoolInner(Semaphore);
@@ -149,9 +152,42 @@ pub fn set_flatten<T: Send + 'static, E, I: IntoIterator<Item = Result<Handle<T>
 ///
 /// When the set is dropped, all handles added to the set
 /// are aborted.
+///
+/// # Progress
+/// With the `print` feature, a progress bar can be attached to the set
+/// with [`with_progress`](Self::with_progress). The total of the bar is the number
+/// of handles added, and it is incremented every time a handle is joined
+/// with [`next`](Self::next), regardless if the task succeeded or failed.
+/// Handles added with a label (for example, with [`add_labeled`](Self::add_labeled))
+/// will show the label as the message of the bar when the task finishes.
+///
+/// ```rust,no_run
+/// # use pistonite_cu as cu;
+/// # async fn example() -> cu::Result<()> {
+/// let pool = cu::co::pool(0);
+/// let mut set = cu::co::set(vec![]).with_progress(cu::progress("building"));
+/// for name in ["foo", "bar", "biz"] {
+///     set.add_labeled(name, pool.spawn(async move { build(name).await }));
+/// }
+/// while let Some(result) = set.next().await {
+///     result??;
+/// }
+/// # Ok(()) }
+/// # async fn build(_: &str) -> cu::Result<()> { Ok(()) }
+/// ```
 pub struct Set<T> {
-    join_set: tokio::task::JoinSet<crate::Result<T>>,
+    join_set: tokio::task::JoinSet<(Option<String>, crate::Result<T>)>,
     abort_handles: Vec<Weak<AbortHandle>>,
+    #[cfg(feature = "print")]
+    progress: Option<SetProgress>,
+}
+
+/// Progress bar attached to a [`Set`]
+#[cfg(feature = "print")]
+struct SetProgress {
+    bar: Arc<crate::ProgressBar>,
+    /// Number of handles added to the set
+    added: u64,
 }
 impl<T> Drop for Set<T> {
     fn drop(&mut self) {
@@ -167,9 +203,28 @@ impl<T: Send + 'static> Set<T> {
         Self {
             join_set: tokio::task::JoinSet::new(),
             abort_handles: Vec::new(),
+            #[cfg(feature = "print")]
+            progress: None,
         }
     }
 
+    /// Spawn a progress bar from the builder to track the handles in the set.
+    /// See [Progress](Self#progress)
+    ///
+    /// The total of the bar is set to the number of handles in the set,
+    /// including the ones already added. The bar is marked as done
+    /// when all handles are joined.
+    #[cfg(feature = "print")]
+    pub fn with_progress(mut self, builder: crate::cli::ProgressBarBuilder) -> Self {
+        let bar = builder.total_if_unset(0).spawn();
+        let added = self.join_set.len() as u64;
+        if added != 0 {
+            bar.set_total(added);
+        }
+        self.progress = Some(SetProgress { bar, added });
+        self
+    }
+
     /// Add one handle to the set.
     ///
     /// A task will be spawned onto the background runtime to start joining
@@ -181,7 +236,15 @@ impl<T: Send + 'static> Set<T> {
     /// abort all handles.
     pub fn add(&mut self, handle: Handle<T>) {
         self.gc_handles(Some(1));
-        self.add_internal(handle);
+        self.add_internal(None, handle);
+    }
+
+    /// Add one handle to the set with a label. The label is displayed
+    /// as the message of the progress bar when the task finishes,
+    /// if a progress bar is attached. See [Progress](Self#progress)
+    pub fn add_labeled(&mut self, label: impl Into<String>, handle: Handle<T>) {
+        self.gc_handles(Some(1));
+        self.add_internal(Some(label.into()), handle);
     }
 
     /// Add multiple handles to the set
@@ -192,7 +255,20 @@ impl<T: Send + 'static> Set<T> {
         let iter = iter.into_iter();
         self.gc_handles(iter.size_hint().1);
         for handle in iter {
-            self.add_internal(handle);
+            self.add_internal(None, handle);
+        }
+    }
+
+    /// Add multiple handles to the set with labels.
+    /// See [`add_labeled`](Self::add_labeled)
+    pub fn extend_labeled<S: Into<String>, I: IntoIterator<Item = (S, Handle<T>)>>(
+        &mut self,
+        iter: I,
+    ) {
+        let iter = iter.into_iter();
+        self.gc_handles(iter.size_hint().1);
+        for (label, handle) in iter {
+            self.add_internal(Some(label.into()), handle);
         }
     }
 
@@ -210,14 +286,14 @@ impl<T: Send + 'static> Set<T> {
         self.gc_handles(iter.size_hint().1);
         for handle in iter {
             match handle {
-                Ok(handle) => self.add_internal(handle),
+                Ok(handle) => self.add_internal(None, handle),
                 Err(e) => errors.push(e),
             }
         }
         errors
     }
 
-    fn add_internal(&mut self, handle: Handle<T>) {
+    fn add_internal(&mut self, label: Option<String>, handle: Handle<T>) {
         let abort_handle = Arc::new(handle.abort_handle());
         self.abort_handles.push(Arc::downgrade(&abort_handle));
         self.join_set.spawn_on(
@@ -225,10 +301,15 @@ impl<T: Send + 'static> Set<T> {
                 let result = handle.co_join().await;
                 // allow the abort handle to be cleaned up later
                 drop(abort_handle);
-                result
+                (label, result)
             },
             runtime::background().handle(),
         );
+        #[cfg(feature = "print")]
+        if let Some(progress) = &mut self.progress {
+            progress.added += 1;
+            progress.bar.set_total(progress.added);
+        }
     }
 
     fn gc_handles(&mut self, add: Option<usize>) {
@@ -244,14 +325,27 @@ impl<T: Send + 'static> Set<T> {
     /// Wait for the next handle to be available, and return it's result,
     /// or `None` if the set is empty, meaning all handles are joined.
     pub async fn next(&mut self) -> Option<crate::Result<T>> {
-        let result = self.join_set.join_next().await?;
-        match result {
+        let Some(result) = self.join_set.join_next().await else {
+            #[cfg(feature = "print")]
+            if let Some(progress) = self.progress.take() {
+                progress.bar.finish();
+            }
+            return None;
+        };
+        let (label, result) = match result {
             Err(join_error) => match co_util::handle_join_error(join_error) {
-                Err(e) => Some(Err(e)),
-                Ok(_) => Some(Err(crate::fmterr!("aborted"))),
+                Err(e) => (None, Err(e)),
+                Ok(_) => (None, Err(crate::fmterr!("aborted"))),
             },
-            Ok(x) => Some(x),
+            Ok(x) => x,
+        };
+        #[cfg(feature = "print")]
+        if let Some(progress) = &self.progress {
+            progress.bar.__inc(1, label);
         }
+        #[cfg(not(feature = "print"))]
+        let _ = label;
+        Some(result)
     }
 
     /// Wait for the next handle to be available, and return it's result,
@@ -1,3 +1,6 @@
+use std::fmt::Display;
+use std::panic::Location;
+
 pub use anyhow::{Context, Error, Ok, Result, anyhow as fmterr, bail};
 
 /// # Error Handling
@@ -49,6 +52,8 @@ pub use anyhow::{Context, Error, Ok, Result, anyhow as fmterr, bail};
 ///   it evaluates to a `Result<()>` instead of generates a return.
 ///   It also does not automatically generate debug information.
 /// - [`cu::some!`] checks an `Option` and returns `Ok(None)` if the option is `None`.
+/// - [`.hint()`](Hint::hint) attaches a hint for the user to the error, which is
+///   printed with the error when returned from the CLI entry point
 ///
 /// Here are other `anyhow` re-exports that are less commonly used
 /// - `anyhow::anyhow` is `cu::fmterr`
@@ -259,3 +264,109 @@ macro_rules! panicand {
         panic!("{s}");
     }}
 }
+
+/// Attach hints to an error
+///
+/// The hints are for actions the user can take to resolve the error.
+/// When the error is returned from the [CLI entry point](macro@crate::cli),
+/// it is printed with the same style as [`cu::hint!`](macro@crate::hint) after the error,
+/// along with where the hint is attached.
+///
+/// Prelude import is required to bring in the trait.
+///
+/// ```rust
+/// # use pistonite_cu as cu;
+/// use cu::pre::*;
+///
+/// fn write_output(force: bool) -> cu::Result<()> {
+///     if !force {
+///         cu::bail!("output already exists");
+///     }
+///     Ok(())
+/// }
+///
+/// let result = write_output(false).hint("run with --force to overwrite");
+/// assert!(result.is_err());
+/// ```
+pub trait Hint<T> {
+    /// Attach a hint to the error, if the result is an `Err`
+    fn hint<H: Display>(self, hint: H) -> Result<T>;
+    /// Attach a hint to the error, which is only evaluated if the result is an `Err`
+    fn with_hint<H: Display, F: FnOnce() -> H>(self, f: F) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Hint<T> for std::result::Result<T, E> {
+    #[track_caller]
+    fn hint<H: Display>(self, hint: H) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| Hinted::attach(e.into(), hint.to_string(), location))
+    }
+
+    #[track_caller]
+    fn with_hint<H: Display, F: FnOnce() -> H>(self, f: F) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| Hinted::attach(e.into(), f().to_string(), location))
+    }
+}
+
+/// An error with hints attached. This is transparent in the error chain,
+/// i.e. displays the same as the inner error
+#[derive(Debug)]
+#[cfg_attr(not(feature = "cli"), allow(dead_code))] // only printed by cu::cli
+pub(crate) struct Hinted {
+    error: Error,
+    hints: Vec<String>,
+    location: &'static Location<'static>,
+}
+
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+impl Hinted {
+    fn attach(error: Error, hint: String, location: &'static Location<'static>) -> Error {
+        // only merge if the outermost error is hinted, since anyhow also
+        // allows downcasting to the inner error of a context, which would drop the context
+        let is_hinted = error.chain().next().is_some_and(|x| x.is::<Self>());
+        let error = if is_hinted {
+            match error.downcast::<Self>() {
+                std::result::Result::Ok(mut hinted) => {
+                    hinted.hints.push(hint);
+                    return Error::new(hinted);
+                }
+                Err(error) => error,
+            }
+        } else {
+            error
+        };
+        Error::new(Self {
+            error,
+            hints: vec![hint],
+            location,
+        })
+    }
+
+    /// Hints attached to this error, in the order they are attached
+    pub(crate) fn hints(&self) -> &[String] {
+        &self.hints
+    }
+
+    /// Where the first hint is attached
+    pub(crate) fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// The error before the hints are attached
+    pub(crate) fn inner(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl Display for Hinted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Hinted {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
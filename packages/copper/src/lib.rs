@@ -104,7 +104,9 @@ pub use lv::{debug, error, info, trace, warn};
 #[cfg(feature = "print")]
 pub mod cli;
 #[cfg(feature = "print")]
-pub use cli::{CtrlcSignal, ProgressBar, progress};
+pub use cli::{CtrlcSignal, ProgressBar, ProgressReader, ProgressWriter, link, progress};
+#[cfg(feature = "print")]
+pub mod style;
 #[cfg(feature = "prompt")]
 pub use cli::{password_chars_legal, prompt, yesno};
 #[cfg(feature = "cli")]
@@ -155,6 +157,8 @@ pub mod __priv {
 pub mod lib {
     #[cfg(feature = "cli")]
     pub use clap;
+    #[cfg(feature = "cli")]
+    pub use clap_complete;
     #[cfg(feature = "derive")]
     pub use derive_more;
 }
@@ -163,8 +167,12 @@ pub mod lib {
 #[doc(hidden)]
 pub mod pre {
     pub use crate::Context as _;
+    pub use crate::Hint as _;
     pub use crate::str::{OsStrExtension as _, OsStrExtensionOwned as _};
 
+    #[cfg(feature = "print")]
+    pub use crate::cli::ProgressIterExtension as _;
+
     #[cfg(feature = "cli")]
     pub use crate::lib::clap;
 
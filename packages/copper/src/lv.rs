@@ -21,7 +21,8 @@
 
 pub use log::{Level as LogLevel, Record as LogRecord, debug, error, info, trace, warn};
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 
 use cu::Atomic;
 
@@ -44,14 +45,35 @@ macro_rules! fmtand {
 
 pub(crate) static PRINT_LEVEL: Atomic<u8, Print> = Atomic::new_u8(Print::Normal as u8);
 pub(crate) static USE_COLOR: AtomicBool = AtomicBool::new(true);
+static USE_UNICODE: AtomicBool = AtomicBool::new(true);
 
 /// Get if color printing is enabled **Only works when cu::cli is being used**.
 pub fn color_enabled() -> bool {
     USE_COLOR.load(Ordering::Acquire)
 }
 
+/// Set the charset of the glyphs used for printing, such as the spinners and the
+/// tree drawing characters. This is called with the `--charset` option
+/// when using [`cu::cli`](macro@crate::cli). The default is unicode.
+#[inline(always)]
+#[cfg(feature = "print")]
+pub fn set_charset(charset: Charset) {
+    USE_UNICODE.store(charset.is_unicode(), Ordering::Release);
+}
+
+/// Get if unicode glyphs are used for printing, instead of ASCII.
+/// **Only works when cu::cli is being used**
+#[inline(always)]
+pub fn unicode_enabled() -> bool {
+    USE_UNICODE.load(Ordering::Acquire)
+}
+
 static ENABLE_TRACE_HINT: AtomicBool = AtomicBool::new(true);
 static ENABLE_PRINT_TIME: AtomicBool = AtomicBool::new(true);
+pub(crate) static ENABLE_RECAP: AtomicBool = AtomicBool::new(false);
+static ENABLE_WINDOW_TITLE: AtomicBool = AtomicBool::new(false);
+static ENABLE_TASKBAR_PROGRESS: AtomicBool = AtomicBool::new(false);
+static PROGRESS_INTERVAL_MS: AtomicU64 = AtomicU64::new(30_000);
 
 /// Disable printing the trace hint line if the CLI entry point returns an error
 ///
@@ -69,7 +91,8 @@ pub fn is_trace_hint_enabled() -> bool {
     ENABLE_TRACE_HINT.load(Ordering::Acquire)
 }
 
-/// Disable printing the time took to run the command
+/// Disable printing the time took to run the command, and the time
+/// each [scope](fn@crate::cli::scope) took
 #[inline(always)]
 #[cfg(feature = "print")]
 pub fn disable_print_time() {
@@ -83,6 +106,95 @@ pub fn is_print_time_enabled() -> bool {
     ENABLE_PRINT_TIME.load(Ordering::Acquire)
 }
 
+/// Print all warnings and errors again when the CLI entry point returns,
+/// so they are not missed after scrolling off the screen.
+///
+/// Only messages printed after this is called are included in the recap
+#[inline(always)]
+#[cfg(feature = "print")]
+pub fn enable_recap() {
+    ENABLE_RECAP.store(true, Ordering::Release);
+}
+
+/// Check if warnings and errors will be printed again on exit.
+/// **Only works when cu::cli is being used**
+#[inline(always)]
+pub fn is_recap_enabled() -> bool {
+    ENABLE_RECAP.load(Ordering::Acquire)
+}
+
+/// Set the terminal window title to the prefix and message of the
+/// current progress bar, while progress bars are animated
+///
+/// The previous title is restored after the bars are done, if the
+/// terminal supports saving the title
+#[inline(always)]
+#[cfg(feature = "print")]
+pub fn enable_window_title() {
+    ENABLE_WINDOW_TITLE.store(true, Ordering::Release);
+}
+
+/// Check if the window title is set to the current progress bar.
+/// **Only works when cu::cli is being used**
+#[inline(always)]
+pub fn is_window_title_enabled() -> bool {
+    ENABLE_WINDOW_TITLE.load(Ordering::Acquire)
+}
+
+/// Show the progress of the current progress bar in the taskbar or tab (OSC 9;4),
+/// while progress bars are animated
+///
+/// This is enabled automatically for terminals known to support it
+/// (Windows Terminal, WezTerm and ConEmu). Other terminals may print
+/// the sequence as garbage, so it is not enabled by default
+#[inline(always)]
+#[cfg(feature = "print")]
+pub fn enable_taskbar_progress() {
+    ENABLE_TASKBAR_PROGRESS.store(true, Ordering::Release);
+}
+
+/// Check if the progress of the current progress bar is shown in the taskbar or tab.
+/// **Only works when cu::cli is being used**
+#[inline(always)]
+pub fn is_taskbar_progress_enabled() -> bool {
+    ENABLE_TASKBAR_PROGRESS.load(Ordering::Acquire)
+}
+
+/// Check if the terminal is known to support showing progress (OSC 9;4)
+/// from the environment variables
+#[cfg(feature = "print")]
+pub(crate) fn is_taskbar_progress_supported() -> bool {
+    is_taskbar_progress_supported_from_env_vars(|name| std::env::var(name).ok())
+}
+#[cfg(feature = "print")]
+fn is_taskbar_progress_supported_from_env_vars(var: impl Fn(&str) -> Option<String>) -> bool {
+    if var("WT_SESSION").is_some_and(|x| !x.is_empty()) {
+        return true;
+    }
+    if var("TERM_PROGRAM").is_some_and(|x| x == "WezTerm") {
+        return true;
+    }
+    var("ConEmuANSI").is_some_and(|x| x == "ON")
+}
+
+/// Set how often the status of progress bars are printed when the bars
+/// cannot be animated, for example when the output is not a terminal (like in CI).
+///
+/// The default is every 30 seconds. Setting it to zero disables the status lines.
+#[inline(always)]
+#[cfg(feature = "print")]
+pub fn set_progress_interval(interval: Duration) {
+    let millis = interval.as_millis().min(u64::MAX as u128) as u64;
+    PROGRESS_INTERVAL_MS.store(millis, Ordering::Release);
+}
+
+/// Get how often the status of progress bars are printed when not animated.
+/// Zero means the status is not printed. **Only works when cu::cli is being used**
+#[inline(always)]
+pub fn progress_interval() -> Duration {
+    Duration::from_millis(PROGRESS_INTERVAL_MS.load(Ordering::Acquire))
+}
+
 /// Color Level settable with `--color` flag
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
@@ -102,14 +214,45 @@ impl std::fmt::Display for Color {
     }
 }
 impl Color {
-    /// Get if color should be used. If `Auto`, returns if stdout is terminal.
+    /// Get if color should be used.
+    ///
+    /// If `Auto`, the following environment variables are checked in order:
+    /// - `CLICOLOR_FORCE` is not empty and not `0`, or `FORCE_COLOR` is not empty: colored
+    /// - `NO_COLOR` is not empty: not colored
+    /// - `CLICOLOR` is `0`: not colored
+    ///
+    /// If none of them applies, returns if stdout is terminal.
     pub fn is_colored_for_stdout(self) -> bool {
         use std::io::IsTerminal;
         match self {
             Self::Always => true,
             Self::Never => false,
-            Self::Auto => std::io::stdout().is_terminal(),
+            Self::Auto => match Self::from_env_conventions() {
+                Some(colored) => colored,
+                None => std::io::stdout().is_terminal(),
+            },
+        }
+    }
+
+    /// Check the color environment variables that are widely used by other tools.
+    /// See <https://no-color.org>, <https://force-color.org> and <https://bixense.com/clicolors>
+    fn from_env_conventions() -> Option<bool> {
+        // not using cu::env_var, since this can be called before log initialization
+        Self::from_env_vars(|name| std::env::var_os(name))
+    }
+    fn from_env_vars(var: impl Fn(&str) -> Option<std::ffi::OsString>) -> Option<bool> {
+        if var("CLICOLOR_FORCE").is_some_and(|x| !x.is_empty() && x != "0")
+            || var("FORCE_COLOR").is_some_and(|x| !x.is_empty())
+        {
+            return Some(true);
+        }
+        if var("NO_COLOR").is_some_and(|x| !x.is_empty()) {
+            return Some(false);
         }
+        if var("CLICOLOR").is_some_and(|x| x == "0") {
+            return Some(false);
+        }
+        None
     }
 
     /// Return the first `--color <COLOR>` or `--color=<COLOR>`
@@ -149,6 +292,99 @@ impl Color {
     }
 }
 
+/// Charset of the glyphs settable with `--charset` flag
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Charset {
+    Unicode,
+    Ascii,
+    #[default]
+    Auto,
+}
+impl std::fmt::Display for Charset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unicode => write!(f, "unicode"),
+            Self::Ascii => write!(f, "ascii"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+impl Charset {
+    /// Get if unicode glyphs should be used.
+    ///
+    /// If `Auto`, ASCII is used if `TERM` is `dumb`, or if the locale (the first non-empty
+    /// one of `LC_ALL`, `LC_CTYPE` and `LANG`) is not UTF-8. If the locale is not set
+    /// on Windows, ASCII is used if the console output code page is not UTF-8.
+    pub fn is_unicode(self) -> bool {
+        match self {
+            Self::Unicode => true,
+            Self::Ascii => false,
+            Self::Auto => {
+                if cfg!(feature = "__test") {
+                    // fix the charset in test
+                    return true;
+                }
+                // not using cu::env_var, since this can be called before log initialization
+                Self::is_unicode_from_env_vars(|name| std::env::var(name).ok())
+                    .unwrap_or_else(Self::is_console_unicode)
+            }
+        }
+    }
+
+    /// Check `TERM` and the locale. `None` if the locale is not set
+    fn is_unicode_from_env_vars(var: impl Fn(&str) -> Option<String>) -> Option<bool> {
+        let var = |name: &str| var(name).filter(|x| !x.is_empty());
+        if var("TERM").is_some_and(|x| x == "dumb") {
+            return Some(false);
+        }
+        let locale = var("LC_ALL")
+            .or_else(|| var("LC_CTYPE"))
+            .or_else(|| var("LANG"))?;
+        let locale = locale.to_ascii_lowercase();
+        Some(locale.contains("utf-8") || locale.contains("utf8"))
+    }
+
+    #[cfg(windows)]
+    fn is_console_unicode() -> bool {
+        use windows_sys::Win32::System::Console::GetConsoleOutputCP;
+        // SAFETY: no preconditions
+        let code_page = unsafe { GetConsoleOutputCP() };
+        // 0 means there is no console, 65001 is UTF-8
+        code_page == 0 || code_page == 65001
+    }
+
+    #[cfg(not(windows))]
+    fn is_console_unicode() -> bool {
+        true
+    }
+}
+
+/// Output format settable with `--log-format` flag
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Format {
+    /// Human-readable text, with colors if enabled
+    #[default]
+    Text,
+    /// One JSON object per line, for log aggregators
+    Json,
+    /// Like `text`, but errors and warnings are printed as GitHub Actions
+    /// workflow commands, so they show up as annotations.
+    ///
+    /// This is the default when running in GitHub Actions
+    Github,
+}
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+            Self::Github => write!(f, "github"),
+        }
+    }
+}
+
 /// Print level settable with `-v` and `-q` flags
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
@@ -340,3 +576,94 @@ pub const I: Lv = Lv::Info;
 pub const D: Lv = Lv::Debug;
 /// Trace
 pub const T: Lv = Lv::Trace;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Look up the env vars from the list instead of the environment
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    fn from_vars(vars: &[(&str, &str)]) -> Option<bool> {
+        let lookup = lookup(vars);
+        Color::from_env_vars(|name| lookup(name).map(Into::into))
+    }
+
+    fn is_unicode_from_vars(vars: &[(&str, &str)]) -> Option<bool> {
+        Charset::is_unicode_from_env_vars(lookup(vars))
+    }
+
+    #[test]
+    fn test_charset_from_env() {
+        assert_eq!(is_unicode_from_vars(&[]), None);
+        assert_eq!(is_unicode_from_vars(&[("LANG", "en_US.UTF-8")]), Some(true));
+        assert_eq!(is_unicode_from_vars(&[("LANG", "C.utf8")]), Some(true));
+        assert_eq!(is_unicode_from_vars(&[("LANG", "C")]), Some(false));
+        assert_eq!(is_unicode_from_vars(&[("LANG", "")]), None);
+        // LC_ALL and LC_CTYPE take precedence
+        assert_eq!(
+            is_unicode_from_vars(&[("LANG", "en_US.UTF-8"), ("LC_ALL", "POSIX")]),
+            Some(false)
+        );
+        assert_eq!(
+            is_unicode_from_vars(&[("LANG", "C"), ("LC_CTYPE", "en_US.UTF-8")]),
+            Some(true)
+        );
+        assert_eq!(
+            is_unicode_from_vars(&[("LANG", "C"), ("LC_ALL", ""), ("LC_CTYPE", "en_US.UTF-8")]),
+            Some(true)
+        );
+        // dumb terminal always uses ASCII
+        assert_eq!(
+            is_unicode_from_vars(&[("TERM", "dumb"), ("LANG", "en_US.UTF-8")]),
+            Some(false)
+        );
+        assert_eq!(
+            is_unicode_from_vars(&[("TERM", "xterm-256color"), ("LANG", "en_US.UTF-8")]),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_color_from_env_conventions() {
+        assert_eq!(from_vars(&[]), None);
+        assert_eq!(from_vars(&[("CLICOLOR_FORCE", "1")]), Some(true));
+        assert_eq!(from_vars(&[("CLICOLOR_FORCE", "0")]), None);
+        assert_eq!(from_vars(&[("CLICOLOR_FORCE", "")]), None);
+        assert_eq!(from_vars(&[("FORCE_COLOR", "1")]), Some(true));
+        assert_eq!(from_vars(&[("FORCE_COLOR", "")]), None);
+        assert_eq!(from_vars(&[("NO_COLOR", "1")]), Some(false));
+        assert_eq!(from_vars(&[("NO_COLOR", "")]), None);
+        assert_eq!(from_vars(&[("CLICOLOR", "0")]), Some(false));
+        assert_eq!(from_vars(&[("CLICOLOR", "1")]), None);
+        // forcing takes precedence
+        assert_eq!(
+            from_vars(&[("NO_COLOR", "1"), ("FORCE_COLOR", "1")]),
+            Some(true)
+        );
+        assert_eq!(
+            from_vars(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "")]),
+            Some(false)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "print")]
+    fn test_taskbar_progress_from_env() {
+        let is_supported =
+            |vars: &[(&str, &str)]| is_taskbar_progress_supported_from_env_vars(lookup(vars));
+        assert!(!is_supported(&[]));
+        assert!(is_supported(&[("WT_SESSION", "0a1b2c3d")]));
+        assert!(!is_supported(&[("WT_SESSION", "")]));
+        assert!(is_supported(&[("TERM_PROGRAM", "WezTerm")]));
+        assert!(!is_supported(&[("TERM_PROGRAM", "Apple_Terminal")]));
+        assert!(is_supported(&[("ConEmuANSI", "ON")]));
+        assert!(!is_supported(&[("ConEmuANSI", "OFF")]));
+    }
+}
@@ -4,6 +4,13 @@ use crate::Context as _;
 
 /// Try to get info from a panic payload
 pub fn best_effort_panic_info<'a>(payload: &'a Box<dyn Any + Send + 'static>) -> &'a str {
+    best_effort_panic_info_unboxed(&**payload)
+}
+
+/// Same as [`best_effort_panic_info`], for the payload in a panic hook
+pub(crate) fn best_effort_panic_info_unboxed<'a>(
+    payload: &'a (dyn Any + Send + 'static),
+) -> &'a str {
     if let Some(s) = payload.downcast_ref::<&str>() {
         s
     } else if let Some(s) = payload.downcast_ref::<String>() {
@@ -11,7 +18,7 @@ pub fn best_effort_panic_info<'a>(payload: &'a Box<dyn Any + Send + 'static>) ->
     } else {
         crate::debug!(
             "encountered unknown panic info with type id: {:?}",
-            (**payload).type_id()
+            payload.type_id()
         );
         "unknown panic info"
     }
@@ -19,7 +26,8 @@ pub fn best_effort_panic_info<'a>(payload: &'a Box<dyn Any + Send + 'static>) ->
 
 /// Copy a reader to a writer.
 ///
-/// This is wrapper for `std::io::copy` with error context
+/// This is wrapper for `std::io::copy` with error context.
+/// Wrap the reader with `cu::ProgressReader` to show the progress
 #[inline(always)]
 pub fn copy<R, W>(reader: &mut R, writer: &mut W) -> crate::Result<u64>
 where
@@ -31,7 +39,8 @@ where
 
 /// Copy an async reader to an async writer.
 ///
-/// This is wrapper for `tokio::io::copy` with error context
+/// This is wrapper for `tokio::io::copy` with error context.
+/// Wrap the reader with `cu::ProgressReader` to show the progress
 #[cfg(feature = "coroutine")]
 #[inline(always)]
 pub async fn co_copy<'a, R, W>(reader: &'a mut R, writer: &'a mut W) -> crate::Result<u64>
@@ -6,7 +6,7 @@ use tokio::io::AsyncBufReadExt as _;
 use tokio::process::{Child as TokioChild, ChildStderr, ChildStdout, Command as TokioCommand};
 
 use crate::BoxedFuture;
-use crate::cli::{ProgressBar, ProgressBarBuilder};
+use crate::cli::{Annotation, ProgressBar, ProgressBarBuilder};
 use crate::lv::Lv;
 use crate::process::{Command, Preset, pio};
 
@@ -78,6 +78,11 @@ use crate::process::{Command, Preset, pio};
 ///     });
 /// ```
 ///
+/// # Annotations
+/// When the output format is `github` (see [`Flags`](crate::cli::Flags)),
+/// errors and warnings are printed as GitHub Actions annotations,
+/// using the location of the primary span of the diagnostic.
+///
 /// # Output
 /// The handle to the progress bar is emitted to the stdout slot.
 /// Be sure to manually call `.done()` on it. See [Progress Bars](fn@crate::progress)
@@ -322,27 +327,29 @@ impl PrintState {
                 let Some(message) = payload.message else {
                     return;
                 };
-                let Some(rendered) = message.rendered else {
+                let Some(rendered) = &message.rendered else {
                     return;
                 };
                 match message.level {
                     Some("warning") => match &self.diagnostic_hook {
                         None => {
-                            crate::cli::__print_with_level(
+                            crate::cli::print_annotated(
                                 self.warning_lv,
-                                format_args!("{rendered}"),
+                                rendered,
+                                &message.annotation(),
                             );
                         }
-                        Some(hook) => hook(true, &rendered),
+                        Some(hook) => hook(true, rendered),
                     },
                     Some("error") => match &self.diagnostic_hook {
                         None => {
-                            crate::cli::__print_with_level(
+                            crate::cli::print_annotated(
                                 self.error_lv,
-                                format_args!("{rendered}"),
+                                rendered,
+                                &message.annotation(),
                             );
                         }
-                        Some(hook) => hook(false, &rendered),
+                        Some(hook) => hook(false, rendered),
                     },
                     _ => {
                         crate::cli::__print_with_level(self.other_lv, format_args!("{rendered}"));
@@ -450,6 +457,65 @@ struct PayloadTarget<'a> {
 #[derive(serde::Deserialize)]
 struct PayloadMessage<'a> {
     level: Option<&'a str>,
-    // for some reason, this can't be deserialize as borrowed
+    // for some reason, these can't be deserialize as borrowed
+    // (likely because they contain escaped characters)
     rendered: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    spans: Vec<PayloadSpan>,
+}
+
+impl PayloadMessage<'_> {
+    fn annotation(&self) -> Annotation<'_> {
+        let title = self.message.as_deref();
+        let Some(span) = self.spans.iter().find(|x| x.is_primary) else {
+            return Annotation {
+                title,
+                ..Default::default()
+            };
+        };
+        Annotation {
+            file: Some(&span.file_name),
+            line: Some(span.line_start),
+            end_line: Some(span.line_end),
+            column: Some(span.column_start),
+            end_column: Some(span.column_end),
+            title,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct PayloadSpan {
+    file_name: String,
+    line_start: u32,
+    line_end: u32,
+    column_start: u32,
+    column_end: u32,
+    is_primary: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lv;
+
+    #[test]
+    fn test_diagnostics_are_counted() {
+        let capture = crate::cli::capture();
+        let warnings = crate::cli::warning_count();
+        let errors = crate::cli::error_count();
+        let mut state =
+            PrintState::new(lv::E, lv::W, lv::I, crate::progress("cargo").spawn(), None);
+        state.handle_stdout(
+            r#"{"reason":"compiler-message","message":{"level":"warning","rendered":"warning: unused variable","message":"unused variable","spans":[{"file_name":"src/main.rs","line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true}]}}"#,
+        );
+        state.handle_stdout(
+            r#"{"reason":"compiler-message","message":{"level":"error","rendered":"error: mismatched types","message":"mismatched types"}}"#,
+        );
+        assert_eq!(capture.count(lv::W), 1);
+        assert_eq!(capture.count(lv::E), 1);
+        assert!(crate::cli::warning_count() > warnings);
+        assert!(crate::cli::error_count() > errors);
+    }
 }
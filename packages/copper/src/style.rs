@@ -0,0 +1,179 @@
+//! # Styled Text
+//!
+//! Functions in this module wrap a value to be displayed with a style,
+//! such as bold, underline, or a color, to highlight part of a message.
+//!
+//! ```rust
+//! # use pistonite_cu as cu;
+//! use cu::style;
+//!
+//! let file = "foo.txt";
+//! cu::info!("reading {}", style::bold(file));
+//! cu::warn!("{} is deprecated, use {} instead", style::red("--foo"), style::green("--bar"));
+//! // styles can be combined
+//! cu::print!("{}", style::underline("important").fg(cu::cli::ThemeColor::Rgb(255, 135, 0)));
+//! ```
+//!
+//! The escape codes are only emitted if color is enabled (see [`cu::lv::color_enabled`](crate::lv::color_enabled)),
+//! so the styles are safe to use even if the output is not a terminal. The styled spans
+//! do not count towards the width when the message is wrapped, and the text color of
+//! the message (for example, yellow for warnings) is restored after a span with a
+//! foreground color ends.
+//!
+//! Colors are downgraded if the terminal does not support them, same as in [`Theme`](crate::cli::Theme).
+use std::fmt::{self, Display};
+
+use crate::cli::{ColorSupport, ThemeColor};
+use crate::lv;
+
+/// Escape code to reset the foreground color, which ends a styled span with a color
+pub(crate) const FG_RESET: &str = "\x1b[39m";
+
+/// A value displayed with a style. See [module level documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T> {
+    value: T,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    fg: Option<ThemeColor>,
+}
+
+impl<T> Styled<T> {
+    /// Make the text bold
+    #[inline(always)]
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+    /// Make the text dim (faint)
+    #[inline(always)]
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+    /// Make the text italic. Not all terminals support this
+    #[inline(always)]
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+    /// Underline the text
+    #[inline(always)]
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+    /// Set the foreground color of the text
+    #[inline(always)]
+    pub fn fg(mut self, color: ThemeColor) -> Self {
+        self.fg = Some(color);
+        self
+    }
+    /// Get the value being styled
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Display> Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !lv::color_enabled() {
+            return self.value.fmt(f);
+        }
+        if self.bold {
+            f.write_str("\x1b[1m")?;
+        }
+        if self.dim {
+            f.write_str("\x1b[2m")?;
+        }
+        if self.italic {
+            f.write_str("\x1b[3m")?;
+        }
+        if self.underline {
+            f.write_str("\x1b[4m")?;
+        }
+        if let Some(fg) = self.fg {
+            f.write_str(&fg.to_ansi(ColorSupport::current()))?;
+        }
+        self.value.fmt(f)?;
+        if self.bold || self.dim {
+            // there is no separate code to end bold and dim
+            f.write_str("\x1b[22m")?;
+        }
+        if self.italic {
+            f.write_str("\x1b[23m")?;
+        }
+        if self.underline {
+            f.write_str("\x1b[24m")?;
+        }
+        if self.fg.is_some() {
+            f.write_str(FG_RESET)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wrap a value to add styles to it
+#[inline(always)]
+pub fn styled<T>(value: T) -> Styled<T> {
+    Styled {
+        value,
+        bold: false,
+        dim: false,
+        italic: false,
+        underline: false,
+        fg: None,
+    }
+}
+
+/// Display the value in bold
+#[inline(always)]
+pub fn bold<T>(value: T) -> Styled<T> {
+    styled(value).bold()
+}
+
+/// Display the value dimmed
+#[inline(always)]
+pub fn dim<T>(value: T) -> Styled<T> {
+    styled(value).dim()
+}
+
+/// Display the value in italic
+#[inline(always)]
+pub fn italic<T>(value: T) -> Styled<T> {
+    styled(value).italic()
+}
+
+/// Display the value underlined
+#[inline(always)]
+pub fn underline<T>(value: T) -> Styled<T> {
+    styled(value).underline()
+}
+
+/// Display the value with a foreground color
+#[inline(always)]
+pub fn fg<T>(value: T, color: ThemeColor) -> Styled<T> {
+    styled(value).fg(color)
+}
+
+macro_rules! color_fn {
+    ($($name:ident: $code:literal),* $(,)?) => {$(
+        #[doc = concat!("Display the value in ", stringify!($name))]
+        #[inline(always)]
+        pub fn $name<T>(value: T) -> Styled<T> {
+            styled(value).fg(ThemeColor::Ansi($code))
+        }
+    )*};
+}
+color_fn! {
+    red: 9,
+    green: 10,
+    yellow: 11,
+    blue: 12,
+    magenta: 13,
+    cyan: 14,
+    gray: 8,
+}
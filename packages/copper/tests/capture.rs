@@ -0,0 +1,173 @@
+#![cfg(feature = "print")]
+use pistonite_cu as cu;
+
+#[test]
+fn test_capture_messages() {
+    let capture = cu::cli::capture();
+    cu::error!("error message");
+    cu::warn!("warn message");
+    cu::info!("multi\nline");
+    cu::trace!("not printed");
+    assert_eq!(capture.count(cu::lv::E), 1);
+    assert_eq!(capture.count(cu::lv::T), 0);
+    assert_eq!(
+        capture.messages(),
+        vec![
+            (cu::lv::E, "error message".to_string()),
+            (cu::lv::W, "warn message".to_string()),
+            (cu::lv::I, "multi\nline".to_string()),
+        ]
+    );
+    capture.clear();
+    assert!(capture.messages().is_empty());
+}
+
+#[test]
+fn test_capture_bars() {
+    let capture = cu::cli::capture();
+    let bar = cu::progress("kept").total(2).spawn();
+    cu::progress!(bar += 2);
+    bar.done_with_message("kept done");
+    let bar = cu::progress("not kept").keep(false).spawn();
+    bar.done();
+    let bar = cu::progress("dropped").spawn();
+    drop(bar);
+    let bars = capture.bars();
+    assert_eq!(bars.len(), 3);
+    assert!(matches!(&bars[0], cu::cli::CapturedBar::Done(Some(x)) if x.contains("kept done")));
+    assert_eq!(bars[1], cu::cli::CapturedBar::Done(None));
+    assert!(matches!(&bars[2], cu::cli::CapturedBar::Interrupted(_)));
+}
+
+#[test]
+fn test_capture_bar_rate() {
+    let capture = cu::cli::capture();
+    let bar = cu::progress("items").total(3).rate(true).spawn();
+    cu::progress!(bar += 3);
+    drop(bar);
+    let bar = cu::progress("bytes").total_bytes(2000).rate(true).spawn();
+    cu::progress!(bar += 2000);
+    drop(bar);
+    let bars = capture.bars();
+    assert!(
+        matches!(&bars[0], cu::cli::CapturedBar::Done(Some(x)) if x.starts_with("[3/3] items: done (") && x.ends_with("/s)"))
+    );
+    assert!(
+        matches!(&bars[1], cu::cli::CapturedBar::Done(Some(x)) if x.starts_with("bytes: done (2.0k / 2.0k, ") && x.ends_with("/s)"))
+    );
+}
+
+#[test]
+fn test_capture_bar_adapters() {
+    use cu::cli::ProgressIterExtension as _;
+    use std::io::{Read as _, Write as _};
+    let capture = cu::cli::capture();
+    let sum: i32 = (1..=3).progress("iter").sum();
+    assert_eq!(sum, 6);
+    // total is not known from size_hint
+    let count = (0..10).filter(|x| x % 2 == 0).progress("filtered").count();
+    assert_eq!(count, 5);
+    let mut iter = (0..3).progress("dropped");
+    iter.next();
+    drop(iter);
+
+    let bar = cu::progress("read").total_bytes(0).spawn();
+    let mut reader = cu::ProgressReader::new(&b"hello"[..], bar);
+    let mut out = String::new();
+    reader.read_to_string(&mut out).unwrap();
+    drop(reader);
+    let bar = cu::progress("write").total_bytes(4).spawn();
+    let mut writer = cu::ProgressWriter::new(Vec::new(), bar);
+    writer.write_all(b"data").unwrap();
+    assert_eq!(writer.into_inner(), b"data");
+
+    let bars = capture.bars();
+    assert_eq!(
+        bars,
+        vec![
+            cu::cli::CapturedBar::Done(Some("[3/3] iter: done".to_string())),
+            cu::cli::CapturedBar::Done(Some("[5/5] filtered: done".to_string())),
+            cu::cli::CapturedBar::Interrupted("[1/3] dropped: interrupted".to_string()),
+            cu::cli::CapturedBar::Done(Some("read: done (5B / 5B)".to_string())),
+            cu::cli::CapturedBar::Done(Some("write: done (4B / 4B)".to_string())),
+        ]
+    );
+}
+
+#[cfg(feature = "coroutine")]
+#[test]
+fn test_capture_set_progress() {
+    let capture = cu::cli::capture();
+    let pool = cu::co::pool(2);
+    let handle = pool.spawn(async { Ok(()) });
+    let mut set = cu::co::set(vec![handle]).with_progress(cu::progress("tasks"));
+    set.add_labeled("failing", pool.spawn(async { cu::bail!("failed") }));
+    set.add_labeled("ok", pool.spawn(async { Ok(()) }));
+    let mut errors = 0;
+    while let Some(result) = set.block() {
+        if result.unwrap().is_err() {
+            errors += 1;
+        }
+    }
+    assert_eq!(errors, 1);
+    assert_eq!(
+        capture.bars(),
+        vec![cu::cli::CapturedBar::Done(Some(
+            "[3/3] tasks: done".to_string()
+        ))]
+    );
+}
+
+#[cfg(feature = "coroutine")]
+#[test]
+fn test_capture_stream_progress() {
+    let capture = cu::cli::capture();
+    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+    for i in 1..=3 {
+        send.send(i).unwrap();
+    }
+    drop(send);
+    let bar = cu::progress("stream").total(0).spawn();
+    let mut stream = cu::cli::ProgressStream::new(move |cx| recv.poll_recv(cx), bar);
+    let sum = cu::co::run(async move {
+        let mut sum = 0;
+        while let Some(i) = stream.next().await {
+            sum += i;
+        }
+        sum
+    });
+    assert_eq!(sum, 6);
+    assert_eq!(
+        capture.bars(),
+        vec![cu::cli::CapturedBar::Done(Some(
+            "[3/3] stream: done".to_string()
+        ))]
+    );
+}
+
+#[cfg(feature = "prompt")]
+#[test]
+fn test_capture_prompts() -> cu::Result<()> {
+    let capture = cu::cli::capture();
+    capture.answer("n").answer("hello");
+    assert!(!cu::yesno!("continue?")?);
+    let answer = cu::prompt!("what to say?")?;
+    assert_eq!(answer.as_str(), "hello");
+    assert!(cu::prompt!("no more answers").is_err());
+
+    let prompts = capture.prompts();
+    assert_eq!(prompts.len(), 3);
+    assert_eq!(prompts[1].answer.as_deref(), Some("hello"));
+    assert_eq!(prompts[2].answer, None);
+    Ok(())
+}
+
+#[test]
+fn test_capture_output() {
+    let capture = cu::cli::capture();
+    cu::info!("listing");
+    cu::output!("a");
+    cu::output!("{}", 2);
+    assert_eq!(capture.output(), "a\n2\n");
+    assert_eq!(capture.messages(), vec![(cu::lv::I, "listing".to_string())]);
+}
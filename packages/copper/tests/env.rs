@@ -0,0 +1,47 @@
+#![cfg(feature = "cli")]
+use pistonite_cu as cu;
+
+// environment is process-wide, so everything is in one test
+#[test]
+fn test_apply_env() {
+    // SAFETY: this is the only test in this binary
+    unsafe {
+        std::env::set_var("CU_TEST_VERBOSE", "2");
+        std::env::set_var("CU_TEST_COLOR", "never");
+        std::env::set_var("CU_TEST_CHARSET", "");
+        std::env::set_var("CU_TEST_LOG_FILE", "out.log");
+    }
+    let mut flags = cu::cli::Flags::default();
+    flags.apply_env("CU_TEST").unwrap();
+    assert_eq!(flags.verbose, 2);
+    assert_eq!(flags.quiet, 0);
+    assert_eq!(flags.color, Some(cu::lv::Color::Never));
+    assert_eq!(flags.charset, None);
+    assert_eq!(flags.log_format, None);
+    assert_eq!(flags.log_file, Some("out.log".into()));
+
+    // command line takes precedence
+    let mut flags = cu::cli::Flags {
+        quiet: 1,
+        color: Some(cu::lv::Color::Always),
+        ..Default::default()
+    };
+    flags.apply_env("CU_TEST").unwrap();
+    assert_eq!(flags.verbose, 0);
+    assert_eq!(flags.quiet, 1);
+    assert_eq!(flags.color, Some(cu::lv::Color::Always));
+
+    // invalid values are reported, but valid ones are still applied
+    unsafe {
+        std::env::set_var("CU_TEST_COLOR", "rainbow");
+        std::env::set_var("CU_TEST_LOG_FORMAT", "json");
+    }
+    let mut flags = cu::cli::Flags::default();
+    let error = flags.apply_env("CU_TEST").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ignored invalid environment variables: CU_TEST_COLOR=rainbow"
+    );
+    assert_eq!(flags.color, None);
+    assert_eq!(flags.log_format, Some(cu::lv::Format::Json));
+}
@@ -0,0 +1,25 @@
+use cu::pre::*;
+use pistonite_cu as cu;
+
+#[test]
+fn test_hint_is_transparent() {
+    let error = example().unwrap_err();
+    assert_eq!(
+        format!("{error:#}"),
+        "failed to build: failed to write: disk full"
+    );
+    let error = example().hint("another hint").unwrap_err();
+    assert_eq!(
+        format!("{error:#}"),
+        "failed to build: failed to write: disk full"
+    );
+    assert!(error.root_cause().is::<std::io::Error>());
+}
+
+fn example() -> cu::Result<()> {
+    let result: std::io::Result<()> = Err(std::io::Error::other("disk full"));
+    result
+        .context("failed to write")
+        .hint("free up some space")
+        .context("failed to build")
+}
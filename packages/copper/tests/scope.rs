@@ -0,0 +1,60 @@
+#![cfg(feature = "print")]
+use pistonite_cu as cu;
+
+#[test]
+fn test_scope() {
+    let capture = cu::cli::capture();
+    assert!(run_scopes(true).is_err());
+    let messages = capture.messages();
+    let messages = messages
+        .iter()
+        .map(|(lv, message)| (*lv, message.split(':').next().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            (cu::lv::I, "outer"),
+            (cu::lv::I, "inner 1"),
+            (cu::lv::I, "inner 1"),
+            (cu::lv::I, "failing"),
+            (cu::lv::E, "failing"),
+            (cu::lv::E, "outer"),
+        ]
+    );
+}
+
+#[test]
+fn test_scope_done() {
+    let capture = cu::cli::capture();
+    assert!(run_scopes(false).is_ok());
+    let messages = capture.messages();
+    let messages = messages
+        .iter()
+        .map(|(lv, message)| (*lv, message.split(':').next().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            (cu::lv::I, "outer"),
+            (cu::lv::I, "inner 1"),
+            (cu::lv::I, "inner 1"),
+            (cu::lv::I, "failing"),
+            (cu::lv::E, "failing"),
+            (cu::lv::I, "outer"),
+        ]
+    );
+}
+
+fn run_scopes(fail: bool) -> cu::Result<()> {
+    let outer = cu::scope!("outer");
+    let inner = cu::scope!("inner {}", 1);
+    inner.done();
+    {
+        let _failing = cu::scope!("failing");
+        if fail {
+            cu::bail!("error");
+        }
+    }
+    outer.done();
+    Ok(())
+}
@@ -0,0 +1,29 @@
+// $ --charset ascii
+// $ --charset unicode
+
+use cu::cli::{Align, Table, Tree};
+
+#[cu::cli]
+fn main(_: cu::cli::Flags) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let mut tree = Tree::new("workspace");
+    let copper = tree.child("copper");
+    copper.child("src").child("lib.rs");
+    copper.child("Cargo.toml");
+    tree.child("target")
+        .collapse()
+        .child("debug")
+        .child("build");
+    tree.child("a directory with a very long name that will be truncated");
+    tree.print();
+
+    let mut table = Table::new(["Name", "Version", "Size"]).align(2, Align::Right);
+    table.row(["serde", "1.0.228", "78K"]);
+    table.row([
+        "a-crate-with-a-very-long-name-that-will-be-truncated",
+        "0.2.2",
+        "1.2M",
+    ]);
+    table.print();
+    Ok(())
+}
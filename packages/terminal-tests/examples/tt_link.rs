@@ -0,0 +1,13 @@
+// $
+// $ --color=always
+
+#[cu::cli]
+fn main(_: cu::cli::Flags) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    cu::info!("report written to {}", cu::link("/tmp/some report.txt"));
+    cu::print!(
+        "{}",
+        cu::link("/tmp/a-long-directory-name/another-long-directory-name/report.txt")
+    );
+    Ok(())
+}
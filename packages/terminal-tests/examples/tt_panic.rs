@@ -0,0 +1,33 @@
+// $
+// $ --color=always
+// $ --caught
+
+use cu::pre::*;
+
+#[derive(clap::Parser, Clone, AsRef)]
+struct Args {
+    /// Panic on another thread and catch it
+    #[clap(long)]
+    caught: bool,
+    #[clap(flatten)]
+    #[as_ref]
+    inner: cu::cli::Flags,
+}
+
+#[cu::cli]
+fn main(args: Args) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    if args.caught {
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("something went wrong in the worker"))?
+            .join();
+        if result.is_ok() {
+            cu::bail!("expected the worker to panic");
+        }
+        cu::info!("recovered from the panic");
+        return Ok(());
+    }
+    cu::info!("about to panic");
+    panic!("something went wrong");
+}
@@ -0,0 +1,22 @@
+// $
+// $ -v
+
+#[cu::cli]
+fn main(_: cu::cli::Flags) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let outer = cu::scope!("building frontend");
+    cu::info!("bundling");
+    {
+        let inner = cu::scope!("compressing assets");
+        cu::debug!("compressed 42 files");
+        cu::warn!("some files are too large:\n- a.png\n- b.png");
+        inner.done();
+    }
+    {
+        let _failing = cu::scope!("uploading");
+        cu::error!("connection refused");
+    }
+    outer.done();
+    cu::info!("finished");
+    Ok(())
+}